@@ -0,0 +1,88 @@
+//! Aider detector
+
+use anyhow::Result;
+
+use crate::config::RuleConfig;
+use crate::detection::{merge_rules, Rule};
+use crate::session::{ClaudeCodeStatus, Pane};
+
+use super::{runs_program, Agent, AgentDetector};
+
+/// Detects Aider by its `aider` process
+#[derive(Default)]
+pub struct AiderDetector {
+    /// Configured rules, checked before the built-in heuristics
+    rules: Vec<Rule>,
+}
+
+impl AiderDetector {
+    pub fn new(configs: &[RuleConfig]) -> Result<Self> {
+        Ok(Self {
+            rules: merge_rules(Vec::new(), configs, Agent::Aider)?,
+        })
+    }
+}
+
+impl AgentDetector for AiderDetector {
+    fn agent(&self) -> Agent {
+        Agent::Aider
+    }
+
+    fn matches(&self, pane: &Pane, process: Option<&str>) -> bool {
+        runs_program(pane, process, "aider")
+    }
+
+    fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    fn fallback_status(&self, content: &str) -> ClaudeCodeStatus {
+        let last_line = content.lines().last().unwrap_or("").trim_end();
+
+        // Confirmations like "Add file to the chat? (Y)es/(N)o [Yes]:"
+        if content.contains("(Y)es/(N)o") {
+            return ClaudeCodeStatus::WaitingInput;
+        }
+        if last_line.contains("Waiting for") {
+            return ClaudeCodeStatus::Working;
+        }
+        // Input prompt, e.g. "> " or "architect> "
+        if last_line.ends_with('>') {
+            return ClaudeCodeStatus::Idle;
+        }
+
+        ClaudeCodeStatus::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation() {
+        let content = "Add src/main.rs to the chat? (Y)es/(N)o/(D)on't ask again [Yes]:";
+        assert_eq!(
+            AiderDetector::default().detect_status(content),
+            ClaudeCodeStatus::WaitingInput
+        );
+    }
+
+    #[test]
+    fn test_working() {
+        let content = "> fix the bug\n░█ Waiting for claude-3-5-sonnet";
+        assert_eq!(
+            AiderDetector::default().detect_status(content),
+            ClaudeCodeStatus::Working
+        );
+    }
+
+    #[test]
+    fn test_idle() {
+        let content = "Tokens: 2.1k sent, 300 received.\nsrc/main.rs\narchitect> ";
+        assert_eq!(
+            AiderDetector::default().detect_status(content),
+            ClaudeCodeStatus::Idle
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Claude Code detector
+
+use anyhow::Result;
+
+use crate::config::RuleConfig;
+use crate::detection::{default_claude_rules, merge_rules, Rule};
+use crate::session::Pane;
+
+use super::{runs_program, Agent, AgentDetector};
+
+/// Detects Claude Code by its `claude` process. Status comes entirely
+/// from rules: the built-in ones merged with any configured ones.
+pub struct ClaudeDetector {
+    rules: Vec<Rule>,
+}
+
+impl ClaudeDetector {
+    pub fn new(configs: &[RuleConfig]) -> Result<Self> {
+        Ok(Self {
+            rules: merge_rules(default_claude_rules(), configs, Agent::Claude)?,
+        })
+    }
+}
+
+impl AgentDetector for ClaudeDetector {
+    fn agent(&self) -> Agent {
+        Agent::Claude
+    }
+
+    fn matches(&self, pane: &Pane, process: Option<&str>) -> bool {
+        // pane_current_command can show a version number instead of "claude",
+        // so the root process is checked as well
+        runs_program(pane, process, "claude")
+    }
+
+    fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+}
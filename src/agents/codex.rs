@@ -0,0 +1,89 @@
+//! Codex CLI detector
+
+use anyhow::Result;
+
+use crate::config::RuleConfig;
+use crate::detection::{merge_rules, Rule};
+use crate::session::{ClaudeCodeStatus, Pane};
+
+use super::{runs_program, Agent, AgentDetector};
+
+/// Detects OpenAI's Codex CLI by its `codex` process
+#[derive(Default)]
+pub struct CodexDetector {
+    /// Configured rules, checked before the built-in heuristics
+    rules: Vec<Rule>,
+}
+
+impl CodexDetector {
+    pub fn new(configs: &[RuleConfig]) -> Result<Self> {
+        Ok(Self {
+            rules: merge_rules(Vec::new(), configs, Agent::Codex)?,
+        })
+    }
+}
+
+impl AgentDetector for CodexDetector {
+    fn agent(&self) -> Agent {
+        Agent::Codex
+    }
+
+    fn matches(&self, pane: &Pane, process: Option<&str>) -> bool {
+        runs_program(pane, process, "codex")
+    }
+
+    fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    fn fallback_status(&self, content: &str) -> ClaudeCodeStatus {
+        // Approval prompts for commands and patches
+        if content.contains("Allow command?")
+            || content.contains("Would you like to")
+            || content.contains("Press Enter to confirm")
+        {
+            return ClaudeCodeStatus::WaitingInput;
+        }
+        if content.contains("esc to interrupt") || content.contains("Esc to interrupt") {
+            return ClaudeCodeStatus::Working;
+        }
+        // Composer footer
+        if content.contains("⏎ send") || content.contains("context left") {
+            return ClaudeCodeStatus::Idle;
+        }
+
+        ClaudeCodeStatus::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_working() {
+        let content = "⠋ Working (12s • esc to interrupt)\n▌ \n⏎ send   ⌃J newline";
+        assert_eq!(
+            CodexDetector::default().detect_status(content),
+            ClaudeCodeStatus::Working
+        );
+    }
+
+    #[test]
+    fn test_waiting_for_approval() {
+        let content = "Would you like to run the following command?\n$ cargo test\n› 1. Yes";
+        assert_eq!(
+            CodexDetector::default().detect_status(content),
+            ClaudeCodeStatus::WaitingInput
+        );
+    }
+
+    #[test]
+    fn test_idle() {
+        let content = "▌ Ask Codex to do anything\n  ⏎ send   ⌃J newline   ⌃C quit";
+        assert_eq!(
+            CodexDetector::default().detect_status(content),
+            ClaudeCodeStatus::Idle
+        );
+    }
+}
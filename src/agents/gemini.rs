@@ -0,0 +1,90 @@
+//! Gemini CLI detector
+
+use anyhow::Result;
+
+use crate::config::RuleConfig;
+use crate::detection::{merge_rules, Rule};
+use crate::session::{ClaudeCodeStatus, Pane};
+
+use super::{runs_program, Agent, AgentDetector};
+
+/// Detects Google's Gemini CLI by its `gemini` process
+#[derive(Default)]
+pub struct GeminiDetector {
+    /// Configured rules, checked before the built-in heuristics
+    rules: Vec<Rule>,
+}
+
+impl GeminiDetector {
+    pub fn new(configs: &[RuleConfig]) -> Result<Self> {
+        Ok(Self {
+            rules: merge_rules(Vec::new(), configs, Agent::Gemini)?,
+        })
+    }
+}
+
+impl AgentDetector for GeminiDetector {
+    fn agent(&self) -> Agent {
+        Agent::Gemini
+    }
+
+    fn matches(&self, pane: &Pane, process: Option<&str>) -> bool {
+        runs_program(pane, process, "gemini")
+    }
+
+    fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    fn fallback_status(&self, content: &str) -> ClaudeCodeStatus {
+        // Tool confirmation dialogs
+        if content.contains("Allow execution")
+            || content.contains("Apply this change?")
+            || content.contains("Yes, allow once")
+        {
+            return ClaudeCodeStatus::WaitingInput;
+        }
+        // Spinner line, e.g. "⠏ Thinking... (esc to cancel, 5s)"
+        if content.contains("esc to cancel") {
+            return ClaudeCodeStatus::Working;
+        }
+        if content.contains("Type your message") {
+            return ClaudeCodeStatus::Idle;
+        }
+
+        ClaudeCodeStatus::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_working() {
+        let content =
+            "⠏ Reading files... (esc to cancel, 5s)\n> Type your message or @path/to/file";
+        assert_eq!(
+            GeminiDetector::default().detect_status(content),
+            ClaudeCodeStatus::Working
+        );
+    }
+
+    #[test]
+    fn test_waiting_input() {
+        let content = "Allow execution of: 'npm'?\n● 1. Yes, allow once\n  2. No";
+        assert_eq!(
+            GeminiDetector::default().detect_status(content),
+            ClaudeCodeStatus::WaitingInput
+        );
+    }
+
+    #[test]
+    fn test_idle() {
+        let content = "> Type your message or @path/to/file\n~/src  no sandbox  gemini-2.5-pro";
+        assert_eq!(
+            GeminiDetector::default().detect_status(content),
+            ClaudeCodeStatus::Idle
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Coding agent detection
+//!
+//! Each supported agent has an [`AgentDetector`] that decides whether a
+//! pane runs that agent and reads the agent's status from the pane content.
+//! Status comes from the detector's rules (built-in plus those in the
+//! config file), then from its built-in heuristics if no rule matches.
+
+mod aider;
+mod claude;
+mod codex;
+mod gemini;
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DetectionConfig;
+use crate::detection::{first_match, strip_ansi, Rule};
+use crate::session::{ClaudeCodeStatus, Pane};
+
+pub use aider::AiderDetector;
+pub use claude::ClaudeDetector;
+pub use codex::CodexDetector;
+pub use gemini::GeminiDetector;
+
+/// A coding agent that can run inside a tmux pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Agent {
+    Claude,
+    Codex,
+    Aider,
+    Gemini,
+}
+
+impl Agent {
+    /// Returns the display symbol for this agent
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Agent::Claude => "\u{f4f5}",
+            Agent::Codex => "\u{f06a9}",
+            Agent::Aider => "\u{f1322}",
+            Agent::Gemini => "\u{f0ae2}",
+        }
+    }
+
+    /// Returns the display name for this agent
+    pub fn label(&self) -> &'static str {
+        match self {
+            Agent::Claude => "Claude Code",
+            Agent::Codex => "Codex CLI",
+            Agent::Aider => "Aider",
+            Agent::Gemini => "Gemini CLI",
+        }
+    }
+}
+
+impl FromStr for Agent {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "claude" => Ok(Self::Claude),
+            "codex" => Ok(Self::Codex),
+            "aider" => Ok(Self::Aider),
+            "gemini" => Ok(Self::Gemini),
+            other => bail!(
+                "unknown agent '{}' (expected claude, codex, aider or gemini)",
+                other
+            ),
+        }
+    }
+}
+
+/// Recognizes one agent and reads its status
+pub trait AgentDetector: Send {
+    /// The agent this detector recognizes
+    fn agent(&self) -> Agent;
+
+    /// Whether the pane runs this agent. `process` is the command line of
+    /// the pane's root process, when known.
+    fn matches(&self, pane: &Pane, process: Option<&str>) -> bool;
+
+    /// Status rules, highest priority first
+    fn rules(&self) -> &[Rule];
+
+    /// Built-in detection used when no rule matches. Receives
+    /// ANSI-stripped content.
+    fn fallback_status(&self, _content: &str) -> ClaudeCodeStatus {
+        ClaudeCodeStatus::Unknown
+    }
+
+    /// Detect the agent's status from the last lines of the pane
+    fn detect_status(&self, content: &str) -> ClaudeCodeStatus {
+        let content = strip_ansi(content);
+        match first_match(self.rules(), &content) {
+            Some(rule) => rule.status,
+            None => self.fallback_status(&content),
+        }
+    }
+}
+
+/// All built-in detectors with the configured rules, in the order they are tried
+pub fn detectors(config: &DetectionConfig) -> Result<Vec<Box<dyn AgentDetector>>> {
+    Ok(vec![
+        Box::new(ClaudeDetector::new(&config.rules)?),
+        Box::new(CodexDetector::new(&config.rules)?),
+        Box::new(AiderDetector::new(&config.rules)?),
+        Box::new(GeminiDetector::new(&config.rules)?),
+    ])
+}
+
+/// Whether the pane's current command or root process mentions `name`
+fn runs_program(pane: &Pane, process: Option<&str>, name: &str) -> bool {
+    pane.current_command.contains(name) || process.is_some_and(|cmd| cmd.contains(name))
+}
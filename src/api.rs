@@ -0,0 +1,438 @@
+//! JSON API over a Unix socket
+//!
+//! `tsm daemon` listens on `tsm.sock` in the state directory so editor
+//! plugins and status bars can query sessions and run actions without
+//! scraping tsm's output. Each request is one JSON object per line with a
+//! `method` field and an optional `id` that is echoed in the response:
+//!
+//! ```text
+//! {"id": 1, "method": "get", "session": "work"}
+//! {"id":1,"ok":true,"result":{"session":{...},"preview":"..."}}
+//! ```
+//!
+//! After `subscribe`, the connection also receives an `event` line for
+//! every pane status change.
+
+use std::fs::DirBuilder;
+use std::io::{BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::app::App;
+use crate::config;
+use crate::daemon::StatusChange;
+use crate::session::Session;
+use crate::tmux::Tmux;
+use crate::worker::PREVIEW_LINES;
+
+/// How long an event may take to reach a subscriber before it is dropped
+const SUBSCRIBER_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Path of the API socket
+pub fn socket_path() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join("tsm.sock"))
+}
+
+/// A request, tagged by its `method` field
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum Request {
+    /// All sessions
+    List,
+    /// One session with a capture of its agent pane
+    Get {
+        session: String,
+        #[serde(default = "default_preview_lines")]
+        lines: usize,
+    },
+    /// Switch a tmux client to a session
+    Switch {
+        session: String,
+        /// Client to switch (see `tmux list-clients`), default the most
+        /// recent one that isn't in control mode
+        #[serde(default)]
+        client: Option<String>,
+    },
+    /// Create a detached session, starting Claude unless `start_claude` is false
+    Create {
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        path: Option<PathBuf>,
+        #[serde(default = "default_true")]
+        start_claude: bool,
+    },
+    Kill {
+        session: String,
+    },
+    Rename {
+        session: String,
+        name: String,
+    },
+    /// Send keys to a session's agent pane
+    SendKeys {
+        session: String,
+        keys: Vec<String>,
+        #[serde(default)]
+        literal: bool,
+    },
+    /// Receive status change events on this connection
+    Subscribe,
+}
+
+fn default_preview_lines() -> usize {
+    PREVIEW_LINES
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Reply to one request
+#[derive(Debug, Serialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// A message pushed to subscribers
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Status(&'a StatusChange),
+}
+
+/// Writing half of a connection, shared with the subscriber list
+type Writer = Arc<Mutex<UnixStream>>;
+
+/// State shared between the daemon and the connection threads
+#[derive(Default)]
+struct Shared {
+    sessions: Vec<Session>,
+    subscribers: Vec<Writer>,
+}
+
+/// The listening API server
+pub struct Server {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Server {
+    /// Listen on `path`, replacing a stale socket, and serve connections
+    /// in the background. Fails when another server answers on `path`.
+    pub fn start(path: &Path) -> Result<Self> {
+        // Only the owner may control sessions through the socket. Its
+        // directory is private so the socket is never reachable by others,
+        // not even between binding it and setting its own mode.
+        if let Some(dir) = path.parent() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+                .with_context(|| format!("Failed to restrict {}", dir.display()))?;
+        }
+
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                bail!("{} exists and is not a socket", path.display());
+            }
+            if UnixStream::connect(path).is_ok() {
+                bail!("Another tsm daemon is listening on {}", path.display());
+            }
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", path.display()))?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let accept_shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&accept_shared);
+                std::thread::spawn(move || serve(stream, shared));
+            }
+        });
+
+        Ok(Self { shared })
+    }
+
+    /// Update the sessions served to clients and push status changes to
+    /// subscribers, dropping those that disconnected or don't keep up.
+    /// Writes happen outside the shared lock so a slow subscriber can't
+    /// hold up requests.
+    pub fn publish(&self, sessions: &[Session], changes: &[StatusChange]) {
+        let subscribers = {
+            let mut shared = self.shared.lock().unwrap();
+            shared.sessions = sessions.to_vec();
+            shared.subscribers.clone()
+        };
+
+        let lines: Vec<String> = changes
+            .iter()
+            .filter_map(|change| serde_json::to_string(&Event::Status(change)).ok())
+            .collect();
+        if lines.is_empty() {
+            return;
+        }
+        let failed: Vec<Writer> = subscribers
+            .into_iter()
+            .filter(|writer| {
+                let mut stream = writer.lock().unwrap();
+                let sent = lines
+                    .iter()
+                    .all(|line| writeln!(stream, "{}", line).is_ok());
+                if !sent {
+                    // A line may be cut off; end the connection
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+                !sent
+            })
+            .collect();
+        if !failed.is_empty() {
+            self.shared
+                .lock()
+                .unwrap()
+                .subscribers
+                .retain(|writer| !failed.iter().any(|f| Arc::ptr_eq(f, writer)));
+        }
+    }
+}
+
+/// Answer requests on one connection until it closes
+fn serve(stream: UnixStream, shared: Arc<Mutex<Shared>>) {
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    // A subscriber that stops reading fails its writes instead of blocking
+    if stream
+        .set_write_timeout(Some(SUBSCRIBER_WRITE_TIMEOUT))
+        .is_err()
+    {
+        return;
+    }
+    let writer: Writer = Arc::new(Mutex::new(stream));
+
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (id, request) = parse_request(&line);
+        let result = request.and_then(|request| match request {
+            Request::Subscribe => {
+                shared.lock().unwrap().subscribers.push(Arc::clone(&writer));
+                Ok(Value::Null)
+            }
+            request => {
+                let sessions = shared.lock().unwrap().sessions.clone();
+                handle(request, &sessions)
+            }
+        });
+
+        let response = match result {
+            Ok(result) => Response {
+                id,
+                ok: true,
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => Response {
+                id,
+                ok: false,
+                result: None,
+                error: Some(format!("{:#}", e)),
+            },
+        };
+        let Ok(text) = serde_json::to_string(&response) else {
+            break;
+        };
+        if writeln!(writer.lock().unwrap(), "{}", text).is_err() {
+            break;
+        }
+    }
+}
+
+/// Parse a request line, keeping its `id` even when the request is invalid
+fn parse_request(line: &str) -> (Option<Value>, Result<Request>) {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => return (None, Err(anyhow!("Invalid JSON: {}", e))),
+    };
+    let id = value.get("id").cloned();
+    let request = serde_json::from_value(value).map_err(|e| anyhow!("Invalid request: {}", e));
+    (id, request)
+}
+
+/// Find a session by name or ID
+fn find_session<'a>(sessions: &'a [Session], key: &str) -> Result<&'a Session> {
+    sessions
+        .iter()
+        .find(|s| s.name == key || s.id == key)
+        .ok_or_else(|| anyhow!("No session '{}'", key))
+}
+
+/// Run a request against tmux. `sessions` is the daemon's latest list.
+fn handle(request: Request, sessions: &[Session]) -> Result<Value> {
+    match request {
+        Request::List => Ok(serde_json::to_value(sessions)?),
+        Request::Get { session, lines } => {
+            let session = find_session(sessions, &session)?;
+            let preview = session
+                .preview_pane()
+                .and_then(|pane_id| Tmux::capture_pane(pane_id, lines, false).ok());
+            Ok(json!({ "session": session, "preview": preview }))
+        }
+        Request::Switch { session, client } => {
+            let session = find_session(sessions, &session)?;
+            Tmux::switch_client(&session.id, client.as_deref())?;
+            Ok(Value::Null)
+        }
+        Request::Create {
+            name,
+            path,
+            start_claude,
+        } => {
+            let name = name.unwrap_or_else(|| App::generate_session_name(start_claude));
+            let path = match path {
+                Some(path) => path,
+                None => dirs::home_dir().context("Could not determine the home directory")?,
+            };
+            Tmux::new_session(&name, &path, start_claude)?;
+            Ok(json!({ "name": name }))
+        }
+        Request::Kill { session } => {
+            let session = find_session(sessions, &session)?;
+            Tmux::kill_session(&session.id)?;
+            Ok(Value::Null)
+        }
+        Request::Rename { session, name } => {
+            let session = find_session(sessions, &session)?;
+            Tmux::rename_session(&session.id, &name)?;
+            Ok(Value::Null)
+        }
+        Request::SendKeys {
+            session,
+            keys,
+            literal,
+        } => {
+            if keys.is_empty() {
+                bail!("No keys to send");
+            }
+            // Keys go to the agent pane; sessions not listed yet are left to
+            // tmux, matched exactly
+            let target = sessions
+                .iter()
+                .find(|s| s.name == session || s.id == session)
+                .and_then(|s| s.preview_pane())
+                .map(str::to_string)
+                .unwrap_or_else(|| Tmux::session_target(&session));
+            Tmux::send_keys(&target, &keys, literal)?;
+            Ok(Value::Null)
+        }
+        Request::Subscribe => bail!("subscribe is handled by the connection"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+
+    use super::*;
+    use crate::session::ClaudeCodeStatus;
+
+    #[test]
+    fn test_parse_request() {
+        let (id, request) = parse_request(r#"{"id": 7, "method": "get", "session": "work"}"#);
+        assert_eq!(id, Some(json!(7)));
+        assert_eq!(
+            request.unwrap(),
+            Request::Get {
+                session: "work".to_string(),
+                lines: PREVIEW_LINES,
+            }
+        );
+
+        let (_, request) =
+            parse_request(r#"{"method": "send_keys", "session": "w", "keys": ["y", "Enter"]}"#);
+        assert_eq!(
+            request.unwrap(),
+            Request::SendKeys {
+                session: "w".to_string(),
+                keys: vec!["y".to_string(), "Enter".to_string()],
+                literal: false,
+            }
+        );
+
+        let (id, request) = parse_request(r#"{"id": "a", "method": "frobnicate"}"#);
+        assert_eq!(id, Some(json!("a")));
+        assert!(request.is_err());
+        assert!(parse_request("not json").1.is_err());
+    }
+
+    #[test]
+    fn test_serve_list_and_subscribe() {
+        let dir = std::env::temp_dir().join(format!("tsm-api-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tsm.sock");
+        let server = Server::start(&path).unwrap();
+        server.publish(&[], &[]);
+        assert!(Server::start(&path).is_err());
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let stream = UnixStream::connect(&path).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut read_line = || {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            serde_json::from_str::<Value>(&line).unwrap()
+        };
+
+        writeln!(writer, r#"{{"id": 1, "method": "list"}}"#).unwrap();
+        assert_eq!(read_line(), json!({ "id": 1, "ok": true, "result": [] }));
+
+        writeln!(writer, r#"{{"id": 2, "method": "get", "session": "nope"}}"#).unwrap();
+        let response = read_line();
+        assert_eq!(response["ok"], json!(false));
+        assert_eq!(response["error"], json!("No session 'nope'"));
+
+        writeln!(writer, r#"{{"method": "subscribe"}}"#).unwrap();
+        assert_eq!(read_line(), json!({ "ok": true, "result": null }));
+
+        let change = StatusChange {
+            time: 1,
+            session_id: "$1".to_string(),
+            session_name: "work".to_string(),
+            pane_id: "%1".to_string(),
+            old: ClaudeCodeStatus::Working,
+            new: ClaudeCodeStatus::WaitingInput,
+        };
+        server.publish(&[], &[change]);
+        let event = read_line();
+        assert_eq!(event["event"], json!("status"));
+        assert_eq!(event["new"], json!("input"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,11 @@
 //! Helper utilities for the app module
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 
+use super::{BulkTarget, SortMode};
+use crate::session::{ClaudeCodeStatus, Session};
+
 /// Expand ~ to home directory in a path string
 pub fn expand_path(path: &str) -> PathBuf {
     if let Some(stripped) = path.strip_prefix("~/") {
@@ -15,3 +19,132 @@ pub fn expand_path(path: &str) -> PathBuf {
     }
     PathBuf::from(path)
 }
+
+/// Fill in a bulk rename pattern: `{name}` is the current name and `{n}`
+/// the session's 1-based position among the renamed sessions
+pub fn apply_rename_pattern(pattern: &str, name: &str, n: usize) -> String {
+    pattern
+        .replace("{name}", name)
+        .replace("{n}", &n.to_string())
+}
+
+/// Check the new names of a bulk rename against each other and against
+/// every existing session, given as `(id, name)`. Marked sessions count
+/// too: renames run one at a time, so a swap or rotation would fail
+/// halfway. Only keeping a session's own name is allowed.
+pub fn check_rename_targets<'a>(
+    targets: &[BulkTarget],
+    existing: impl IntoIterator<Item = (&'a str, &'a str)> + Clone,
+) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for target in targets {
+        let name = target.new_name.as_deref().unwrap_or_default();
+        if name.is_empty() {
+            return Err("Empty name".to_string());
+        }
+        if !names.insert(name) {
+            return Err(format!("'{}' would be used twice, try {{n}}", name));
+        }
+        let taken = existing
+            .clone()
+            .into_iter()
+            .any(|(id, existing_name)| existing_name == name && id != target.session_id);
+        if taken {
+            return Err(format!("'{}' already exists", name));
+        }
+    }
+    Ok(())
+}
+
+/// Order sessions for the list. By creation time, each directory group is
+/// kept together in the order of its oldest session. Waiting longest is a
+/// flat list so every waiting session is above the rest, whatever its
+/// directory.
+pub fn sort_sessions(sessions: &mut [&Session], mode: SortMode) {
+    match mode {
+        SortMode::Created => {
+            sessions.sort_by_key(|s| s.created);
+            let mut group_order: Vec<String> = Vec::new();
+            for session in sessions.iter() {
+                let path = session.display_path();
+                if !group_order.contains(&path) {
+                    group_order.push(path);
+                }
+            }
+            sessions.sort_by_key(|s| {
+                let path = s.display_path();
+                group_order.iter().position(|p| *p == path)
+            });
+        }
+        SortMode::WaitingLongest => sessions.sort_by_key(|s| {
+            let waiting = s.claude_code_status == ClaudeCodeStatus::WaitingInput;
+            let since = if waiting {
+                s.status_since.unwrap_or(u64::MAX)
+            } else {
+                0
+            };
+            (!waiting, since, s.created)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(id: &str, name: &str, new_name: &str) -> BulkTarget {
+        BulkTarget {
+            session_id: id.to_string(),
+            session_name: name.to_string(),
+            new_name: Some(new_name.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_check_rename_targets() {
+        let existing = [("$1", "a"), ("$2", "b"), ("$3", "c")];
+        let check = |targets: &[BulkTarget]| check_rename_targets(targets, existing);
+
+        assert!(check(&[target("$1", "a", "a"), target("$2", "b", "x")]).is_ok());
+        // A swap of two marked sessions
+        assert_eq!(
+            check(&[target("$1", "a", "b"), target("$2", "b", "a")]),
+            Err("'b' already exists".to_string())
+        );
+        assert!(check(&[target("$1", "a", "c")]).is_err());
+        assert!(check(&[target("$1", "a", "x"), target("$2", "b", "x")]).is_err());
+        assert!(check(&[target("$1", "a", "")]).is_err());
+    }
+
+    #[test]
+    fn test_apply_rename_pattern() {
+        assert_eq!(apply_rename_pattern("{name}-old", "api", 1), "api-old");
+        assert_eq!(apply_rename_pattern("work-{n}", "api", 3), "work-3");
+        assert_eq!(apply_rename_pattern("fixed", "api", 1), "fixed");
+    }
+
+    #[test]
+    fn test_sort_sessions() {
+        let session = |id: &str, dir: &str, status: ClaudeCodeStatus, since: u64| Session {
+            created: id[1..].parse().unwrap(),
+            working_directory: PathBuf::from(dir),
+            status_since: Some(since),
+            ..crate::session::fixtures::session(id, status)
+        };
+        let all = [
+            session("$1", "/a", ClaudeCodeStatus::Working, 10),
+            session("$2", "/b", ClaudeCodeStatus::WaitingInput, 30),
+            session("$3", "/a", ClaudeCodeStatus::WaitingInput, 20),
+            session("$4", "/b", ClaudeCodeStatus::Idle, 10),
+        ];
+        let sorted = |mode| {
+            let mut sessions: Vec<&Session> = all.iter().collect();
+            sort_sessions(&mut sessions, mode);
+            sessions.iter().map(|s| s.id.as_str()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(SortMode::Created), ["$1", "$3", "$2", "$4"]);
+        // Waiters of both directories come before the working session in /a
+        assert_eq!(sorted(SortMode::WaitingLongest), ["$3", "$2", "$1", "$4"]);
+    }
+}
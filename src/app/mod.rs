@@ -3,15 +3,32 @@
 mod helpers;
 mod mode;
 
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
 
+use crate::config::Config;
+use crate::daemon;
+use crate::detection::{self, TypedOption};
+use crate::discovery::Discovery;
+use crate::notify::{self, Notifier};
+use crate::pager::Pager;
+use crate::queue::{self, Queues};
 use crate::scroll_state::ScrollState;
-use crate::session::Session;
+use crate::session::{unix_now, ClaudeCodeStatus, Session, StatusClock, StatusCounts, TreeNode};
 use crate::tmux::Tmux;
+use crate::worker::{Update, Worker};
+
+pub use mode::{
+    BroadcastScope, BulkAction, BulkTarget, Destination, Mode, NewSessionField, NodeRef,
+    SendTarget, SendTo, SessionAction, SessionResult, SortMode,
+};
 
-pub use mode::{Mode, NewSessionField, SessionAction};
+use helpers::{apply_rename_pattern, check_rename_targets, expand_path, sort_sessions};
 
-use helpers::expand_path;
+/// Lines captured to find the menu for a quick reply
+const QUICK_REPLY_LINES: usize = 40;
 
 /// Main application state
 pub struct App {
@@ -39,10 +56,34 @@ pub struct App {
     pub selected_action: usize,
     /// Action pending confirmation
     pub pending_action: Option<SessionAction>,
+    /// Window or pane an action started from the tree works on
+    pub tree_node: Option<NodeRef>,
+    /// History shown in preview mode
+    pub pager: Option<Pager>,
     /// Scroll state for the session list
     pub scroll_state: ScrollState,
     /// Whether to show the preview pane
     pub show_preview: bool,
+    /// When the session list was last refreshed (None until the first snapshot)
+    pub last_refresh: Option<Instant>,
+    /// Whether session changes are pushed by a tmux control-mode client
+    pub live_updates: bool,
+    /// Whether sessions come from a running `tsm daemon`
+    pub from_daemon: bool,
+    /// Background thread that refreshes sessions and the preview
+    worker: Worker,
+    /// Pane the preview is currently captured from
+    preview_pane: Option<String>,
+    /// Sends notifications when agents need attention
+    notifier: Notifier,
+    /// Order of the session list
+    pub sort_mode: SortMode,
+    /// Tracks when each session's status last changed
+    status_clock: StatusClock,
+    /// IDs of the sessions marked with `m`
+    pub marked: HashSet<String>,
+    /// Prompt queues as last read from disk
+    pub queues: Queues,
 }
 
 impl App {
@@ -52,11 +93,19 @@ impl App {
 
     /// Create a new App instance
     pub fn new() -> Result<Self> {
-        let sessions = Tmux::list_sessions()?;
-        let current_session = Tmux::current_session()?;
+        const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
 
-        let mut app = Self {
-            sessions,
+        let current_session = Tmux::current_session()?;
+        let config = Config::load()?;
+        let discovery = Discovery::new(&config)?;
+        // Start with the daemon's sessions, if one is running
+        let snapshot = daemon::read_snapshot();
+
+        let app = Self {
+            sessions: snapshot
+                .as_ref()
+                .map(|s| s.sessions.clone())
+                .unwrap_or_default(),
             selected: 0,
             mode: Mode::Normal,
             should_quit: false,
@@ -68,52 +117,170 @@ impl App {
             available_actions: Vec::new(),
             selected_action: 0,
             pending_action: None,
+            tree_node: None,
+            pager: None,
             scroll_state: ScrollState::new(),
             show_preview: true,
+            last_refresh: snapshot.as_ref().map(|_| Instant::now()),
+            live_updates: false,
+            from_daemon: snapshot.is_some(),
+            worker: Worker::spawn(discovery, REFRESH_INTERVAL, true),
+            notifier: Notifier::new(config.notifications),
+            sort_mode: SortMode::default(),
+            status_clock: StatusClock::default(),
+            marked: HashSet::new(),
+            queues: queue::load(),
+            preview_pane: None,
         };
 
-        app.update_preview();
         Ok(app)
     }
 
-    /// Update the preview content for the currently selected session
+    /// Point the preview at the currently selected session's pane.
+    /// The worker captures it in the background.
     pub fn update_preview(&mut self) {
-        const PREVIEW_LINES: usize = 15;
-
-        let pane_id = self.selected_session().and_then(|session| {
-            session
-                .claude_code_pane
-                .clone()
-                .or_else(|| session.panes.first().map(|p| p.id.clone()))
-        });
+        let pane_id = self
+            .selected_session()
+            .and_then(|session| session.preview_pane().map(str::to_string));
 
-        self.preview_content = pane_id.and_then(|id| {
-            Tmux::capture_pane(&id, PREVIEW_LINES, false).ok()
-        });
+        if pane_id != self.preview_pane {
+            self.preview_content = None;
+            self.preview_pane = pane_id.clone();
+            self.worker.set_preview_pane(pane_id);
+        }
     }
 
-    /// Called every tick to refresh sessions and preview
+    /// Called every tick to apply updates from the worker
     pub fn tick(&mut self) {
-        if let Ok(sessions) = Tmux::list_sessions() {
-            // Preserve selected session by name
-            let selected_name = self.selected_session().map(|s| s.name.clone());
-            self.sessions = sessions;
+        let before = notify::pane_statuses(&self.sessions);
+        let sessions_before = queue::session_statuses(&self.sessions);
+
+        for update in self.worker.updates() {
+            match update {
+                Update::Sessions(snapshot) => {
+                    match snapshot.sessions {
+                        Ok(mut sessions) => {
+                            // The daemon's sessions already carry their times
+                            if !snapshot.from_daemon {
+                                self.status_clock.stamp(&mut sessions, unix_now());
+                            }
+                            self.apply_sessions(sessions);
+                        }
+                        Err(e) => self.error = Some(format!("\u{f00d} Failed to refresh: {}", e)),
+                    }
+                    // The daemon or a `tsm` elsewhere may have sent a prompt
+                    self.queues = queue::load();
+                    self.last_refresh = Some(snapshot.taken_at);
+                    self.live_updates = snapshot.live;
+                    self.from_daemon = snapshot.from_daemon;
+                }
+                Update::SessionRenamed { session_id, name } => {
+                    if let Some(session) = self.sessions.iter_mut().find(|s| s.id == session_id) {
+                        session.name = name;
+                    }
+                }
+                Update::PaneStatus {
+                    pane_id,
+                    status,
+                    question,
+                } => {
+                    let selected = self.selected_name();
+                    for session in &mut self.sessions {
+                        if session.set_pane_status(&pane_id, status, question.clone()) {
+                            break;
+                        }
+                    }
+                    self.status_clock.stamp(&mut self.sessions, unix_now());
+                    self.restore_selection(selected);
+                }
+                Update::Preview { pane_id, content } => {
+                    if pane_id == self.preview_pane {
+                        self.preview_content = content;
+                    }
+                }
+            }
+        }
+        // A running daemon sends the notifications and queued prompts
+        if !self.from_daemon {
+            self.notifier
+                .notify(notify::changes(&before, &self.sessions));
+            self.dispatch_queued(&sessions_before);
+        }
+        self.update_preview();
+    }
 
-            // Restore selection by name
-            if let Some(ref name) = selected_name {
-                let filtered = self.filtered_sessions();
-                if let Some(pos) = filtered.iter().position(|s| &s.name == name) {
-                    self.selected = pos;
+    /// Send queued prompts to sessions that finished working
+    fn dispatch_queued(&mut self, before: &HashMap<String, ClaudeCodeStatus>) {
+        let dispatched = match queue::dispatch(before, &self.sessions) {
+            Ok(dispatched) => dispatched,
+            Err(e) => {
+                self.error = Some(format!("\u{f00d} Prompt queue: {:#}", e));
+                return;
+            }
+        };
+        for dispatch in &dispatched {
+            match dispatch.error {
+                None => {
+                    self.message = Some(format!(
+                        "\u{f0cb} Sent queued prompt to '{}'",
+                        dispatch.session_name
+                    ))
+                }
+                Some(ref e) => {
+                    self.error = Some(format!(
+                        "\u{f00d} Failed to send queued prompt to '{}': {}",
+                        dispatch.session_name, e
+                    ))
                 }
             }
+        }
+        if !dispatched.is_empty() {
+            self.queues = queue::load();
+        }
+    }
+
+    /// Replace the session list, keeping the selection on the same session
+    fn apply_sessions(&mut self, sessions: Vec<Session>) {
+        let selected = self.selected_name();
+        self.sessions = sessions;
+        let sessions = &self.sessions;
+        self.marked
+            .retain(|id| sessions.iter().any(|s| &s.id == id));
+        self.restore_selection(selected);
+    }
+
+    /// Name of the selected session
+    fn selected_name(&self) -> Option<String> {
+        self.selected_session().map(|s| s.name.clone())
+    }
 
-            if self.selected >= self.filtered_sessions().len() && !self.sessions.is_empty() {
-                self.selected = self.filtered_sessions().len().saturating_sub(1);
+    /// Select the named session again after the list order may have changed
+    fn restore_selection(&mut self, name: Option<String>) {
+        if let Some(ref name) = name {
+            let filtered = self.filtered_sessions();
+            if let Some(pos) = filtered.iter().position(|s| &s.name == name) {
+                self.selected = pos;
             }
         }
+
+        if self.selected >= self.filtered_sessions().len() && !self.sessions.is_empty() {
+            self.selected = self.filtered_sessions().len().saturating_sub(1);
+        }
+    }
+
+    /// Switch to the next sort mode, keeping the selection
+    pub fn cycle_sort(&mut self) {
+        let selected = self.selected_name();
+        self.sort_mode = self.sort_mode.next();
+        self.restore_selection(selected);
         self.update_preview();
     }
 
+    /// How old the displayed session data is
+    pub fn data_age(&self) -> Option<Duration> {
+        self.last_refresh.map(|t| t.elapsed())
+    }
+
     /// Clear any displayed messages
     pub fn clear_messages(&mut self) {
         self.error = None;
@@ -123,27 +290,13 @@ impl App {
     /// Refresh the session list
     pub fn refresh(&mut self) {
         self.clear_messages();
-        if self.refresh_sessions() {
-            self.message = Some("\u{f00c} Refreshed".to_string());
-        }
+        self.refresh_sessions();
+        self.message = Some("\u{f021} Refreshing".to_string());
     }
 
-    /// Refresh sessions without affecting messages
-    fn refresh_sessions(&mut self) -> bool {
-        match Tmux::list_sessions() {
-            Ok(sessions) => {
-                self.sessions = sessions;
-                if self.selected >= self.sessions.len() && !self.sessions.is_empty() {
-                    self.selected = self.sessions.len() - 1;
-                }
-                self.update_preview();
-                true
-            }
-            Err(e) => {
-                self.error = Some(format!("\u{f00d} Failed to refresh: {}", e));
-                false
-            }
-        }
+    /// Ask the worker to refresh sessions without affecting messages
+    fn refresh_sessions(&mut self) {
+        self.worker.request_refresh();
     }
 
     // =========================================================================
@@ -164,19 +317,21 @@ impl App {
                 })
                 .collect()
         };
-        result.sort_by_key(|s| s.created);
+
+        sort_sessions(&mut result, self.sort_mode);
         result
     }
 
-    /// Get filtered sessions grouped by working directory.
-    /// Returns Vec of (group_path, Vec<(flat_index, &Session)>).
+    /// Get filtered sessions grouped by working directory. Only neighbouring
+    /// sessions share a group, so a directory shows up twice when the sort
+    /// mode splits it. Returns Vec of (group_path, Vec<(flat_index, &Session)>).
     pub fn grouped_sessions(&self) -> Vec<(String, Vec<(usize, &Session)>)> {
         let filtered = self.filtered_sessions();
         let mut groups: Vec<(String, Vec<(usize, &Session)>)> = Vec::new();
 
         for (i, session) in filtered.iter().enumerate() {
             let path = session.display_path();
-            if let Some(group) = groups.iter_mut().find(|(p, _)| p == &path) {
+            if let Some(group) = groups.last_mut().filter(|(p, _)| p == &path) {
                 group.1.push((i, session));
             } else {
                 groups.push((path, vec![(i, session)]));
@@ -200,6 +355,14 @@ impl App {
             .collect()
     }
 
+    /// Filtered sessions in the order they are shown
+    fn visual_sessions(&self) -> Vec<&Session> {
+        self.grouped_sessions()
+            .into_iter()
+            .flat_map(|(_, sessions)| sessions.into_iter().map(|(_, s)| s))
+            .collect()
+    }
+
     /// Move selection up (in visual/grouped order)
     pub fn select_prev(&mut self) {
         let order = self.visual_order();
@@ -254,6 +417,320 @@ impl App {
         }
     }
 
+    // =========================================================================
+    // Window tree
+    // =========================================================================
+
+    /// Browse the windows and panes of the selected session, starting at the
+    /// pane the session is on
+    pub fn start_tree(&mut self) {
+        self.clear_messages();
+        let Some(session) = self.selected_session() else {
+            return;
+        };
+        let tree = session.tree();
+        let current_window = tree.iter().find_map(|node| match node {
+            TreeNode::Window {
+                index,
+                active: true,
+                ..
+            } => Some(*index),
+            _ => None,
+        });
+        let selected = tree
+            .iter()
+            .position(|node| {
+                matches!(node, TreeNode::Pane(pane)
+                    if pane.active && Some(pane.window_index) == current_window)
+            })
+            .unwrap_or(0);
+        self.mode = Mode::Tree { selected };
+    }
+
+    /// Position of the tree highlight, kept inside the tree when windows
+    /// or panes go away
+    pub fn tree_selection(&self) -> Option<usize> {
+        let Mode::Tree { selected } = self.mode else {
+            return None;
+        };
+        let count = self.selected_session()?.tree().len();
+        Some(selected.min(count.saturating_sub(1)))
+    }
+
+    /// The highlighted window or pane and its session
+    fn selected_tree_node(&self) -> Option<(&Session, TreeNode<'_>)> {
+        let selected = self.tree_selection()?;
+        let session = self.selected_session()?;
+        let node = *session.tree().get(selected)?;
+        Some((session, node))
+    }
+
+    /// The highlighted window or pane, for an action on it
+    fn node_ref(&self) -> Option<NodeRef> {
+        let position = self.tree_selection()?;
+        let (session, node) = self.selected_tree_node()?;
+        let window_index = match node {
+            TreeNode::Window { index, .. } => index,
+            TreeNode::Pane(pane) => pane.window_index,
+        };
+        let window_name = session
+            .windows
+            .iter()
+            .find(|w| w.index == window_index)
+            .map(|w| w.name.clone())
+            .unwrap_or_default();
+        let pane_id = match node {
+            TreeNode::Pane(pane) => Some(pane.id.clone()),
+            TreeNode::Window { .. } => None,
+        };
+        let label = match pane_id {
+            Some(ref pane_id) => format!("{}:{}.{}", session.name, window_index, pane_id),
+            None => format!("{}:{}", session.name, window_index),
+        };
+        Some(NodeRef {
+            position,
+            session_id: session.id.clone(),
+            window: format!("{}:{}", session.id, window_index),
+            window_name,
+            pane_id,
+            last_in_session: match node {
+                TreeNode::Pane(_) => session.panes.len() <= 1,
+                TreeNode::Window { .. } => session.window_count <= 1,
+            },
+            label,
+        })
+    }
+
+    /// Move the tree highlight by `delta` nodes
+    pub fn move_tree_selection(&mut self, delta: isize) {
+        let Some(current) = self.tree_selection() else {
+            return;
+        };
+        let count = self.selected_session().map_or(0, |s| s.tree().len());
+        self.mode = Mode::Tree {
+            selected: current
+                .saturating_add_signed(delta)
+                .min(count.saturating_sub(1)),
+        };
+    }
+
+    /// Switch to the highlighted window or pane, and quit unless `stay`
+    pub fn switch_to_tree_node(&mut self, stay: bool) {
+        self.clear_messages();
+        let Some((session, node)) = self.selected_tree_node() else {
+            return;
+        };
+        let target = session.tree_target(&node);
+        let label = match node {
+            TreeNode::Window { index, .. } => format!("{}:{}", session.name, index),
+            TreeNode::Pane(pane) => format!("{}:{}.{}", session.name, pane.window_index, pane.id),
+        };
+        match Tmux::switch_to_session(&target) {
+            Ok(_) if stay => {
+                self.message = Some(format!("\u{f00c} Switched to '{}'", label));
+            }
+            Ok(_) => {
+                self.should_quit = true;
+            }
+            Err(e) => {
+                self.error = Some(format!("\u{f00d} Failed to switch: {}", e));
+            }
+        }
+    }
+
+    /// Kill the highlighted pane, or window when a window is highlighted
+    pub fn start_tree_kill(&mut self) {
+        let action = match self.selected_tree_node() {
+            Some((_, TreeNode::Pane(_))) => SessionAction::KillPane,
+            Some((_, TreeNode::Window { .. })) => SessionAction::KillWindow,
+            None => return,
+        };
+        self.start_tree_action(action);
+    }
+
+    /// Start a window or pane action on the highlighted node
+    pub fn start_tree_action(&mut self, action: SessionAction) {
+        self.clear_messages();
+        let Some(node) = self.node_ref() else {
+            return;
+        };
+        let pane_action = matches!(
+            action,
+            SessionAction::KillPane | SessionAction::BreakPane | SessionAction::JoinPane
+        );
+        if pane_action && node.pane_id.is_none() {
+            self.error = Some("\u{f00d} Select a pane first".to_string());
+            return;
+        }
+
+        let mode = match action {
+            SessionAction::RenameWindow => Mode::RenameWindow {
+                new_name: node.window_name.clone(),
+            },
+            SessionAction::MoveWindow | SessionAction::BreakPane | SessionAction::JoinPane => {
+                // A pane can break out into a window of its own session
+                let destinations: Vec<Destination> = self
+                    .visual_sessions()
+                    .into_iter()
+                    .filter(|s| action == SessionAction::BreakPane || s.id != node.session_id)
+                    .map(|s| Destination {
+                        session_id: s.id.clone(),
+                        session_name: s.name.clone(),
+                    })
+                    .collect();
+                if destinations.is_empty() {
+                    self.error = Some("\u{f00d} No other session to move to".to_string());
+                    return;
+                }
+                Mode::PickSession {
+                    action,
+                    destinations,
+                    selected: 0,
+                }
+            }
+            _ if action.requires_confirmation() => {
+                self.pending_action = Some(action);
+                Mode::ConfirmAction
+            }
+            _ => return,
+        };
+        self.tree_node = Some(node);
+        self.mode = mode;
+    }
+
+    /// Move the highlight in the session picker
+    pub fn move_destination_selection(&mut self, delta: isize) {
+        if let Mode::PickSession {
+            ref destinations,
+            ref mut selected,
+            ..
+        } = self.mode
+        {
+            *selected = selected
+                .saturating_add_signed(delta)
+                .min(destinations.len().saturating_sub(1));
+        }
+    }
+
+    /// Move the picked window or pane to the highlighted session
+    pub fn confirm_pick_session(&mut self) {
+        let Mode::PickSession {
+            ref action,
+            ref destinations,
+            selected,
+        } = self.mode
+        else {
+            return;
+        };
+        let (Some(node), Some(destination)) = (self.tree_node.clone(), destinations.get(selected))
+        else {
+            return self.cancel();
+        };
+        let pane_id = node.pane_id.as_deref().unwrap_or_default();
+        let result = match action {
+            SessionAction::MoveWindow => Tmux::move_window(&node.window, &destination.session_id),
+            SessionAction::BreakPane => Tmux::break_pane(pane_id, &destination.session_id),
+            SessionAction::JoinPane => Tmux::join_pane(pane_id, &destination.session_id),
+            _ => return self.cancel(),
+        };
+        let verb = if *action == SessionAction::JoinPane {
+            "Joined"
+        } else {
+            "Moved"
+        };
+        let message = format!(
+            "{} '{}' to '{}'",
+            verb, node.label, destination.session_name
+        );
+        self.finish_tree_action(result.map(|_| message));
+    }
+
+    /// Rename the picked window
+    pub fn confirm_rename_window(&mut self) {
+        let Mode::RenameWindow { ref new_name } = self.mode else {
+            return;
+        };
+        let Some(node) = self.tree_node.clone() else {
+            return self.cancel();
+        };
+        if *new_name == node.window_name {
+            return self.finish_tree_action(Ok(String::new()));
+        }
+        let message = format!("Renamed '{}' to '{}'", node.label, new_name);
+        let result = Tmux::rename_window(&node.window, new_name);
+        self.finish_tree_action(result.map(|_| message));
+    }
+
+    /// Report how a tree action went and go back to the tree, or to the
+    /// session list when the session may be gone
+    fn finish_tree_action(&mut self, result: Result<String>) {
+        let node = self.tree_node.take();
+        match result {
+            Ok(message) => {
+                self.refresh_sessions();
+                if !message.is_empty() {
+                    self.message = Some(format!("\u{f00c} {}", message));
+                }
+            }
+            Err(e) => self.error = Some(format!("\u{f00d} {:#}", e)),
+        }
+        self.mode = match node {
+            Some(node) if !node.last_in_session => Mode::Tree {
+                selected: node.position,
+            },
+            _ => Mode::Normal,
+        };
+    }
+
+    // =========================================================================
+    // Dialog flows: New window
+    // =========================================================================
+
+    /// Start the new window flow; from the tree, it returns there afterwards
+    pub fn start_new_window(&mut self) {
+        self.clear_messages();
+        if self.selected_session().is_none() {
+            return;
+        }
+        self.tree_node = self.node_ref().map(|node| NodeRef {
+            last_in_session: false,
+            ..node
+        });
+        self.mode = Mode::NewWindow {
+            name: String::new(),
+            start_claude: true,
+        };
+    }
+
+    /// Create the new window in the selected session
+    pub fn confirm_new_window(&mut self) {
+        let Mode::NewWindow {
+            ref name,
+            start_claude,
+        } = self.mode
+        else {
+            return;
+        };
+        let Some(session) = self.selected_session() else {
+            return self.cancel();
+        };
+        let name = (!name.is_empty()).then_some(name.as_str());
+        let message = format!("Created window in '{}'", session.name);
+        let result = Tmux::new_window(&session.id, name, &session.working_directory, start_claude);
+        if self.tree_node.is_some() {
+            self.finish_tree_action(result.map(|_| message));
+        } else {
+            match result {
+                Ok(_) => {
+                    self.refresh_sessions();
+                    self.message = Some(format!("\u{f00c} {}", message));
+                }
+                Err(e) => self.error = Some(format!("\u{f00d} {:#}", e)),
+            }
+            self.mode = Mode::Normal;
+        }
+    }
+
     // =========================================================================
     // Action menu
     // =========================================================================
@@ -299,16 +776,31 @@ impl App {
 
     /// Compute available actions for the selected session
     fn compute_actions(&mut self) {
-        if self.selected_session().is_none() {
+        let Some(session) = self.selected_session() else {
             self.available_actions = vec![];
             return;
-        }
+        };
 
-        let actions = vec![
-            SessionAction::SwitchTo,
+        let mut actions = vec![SessionAction::SwitchTo];
+        if session.claude_code_status == ClaudeCodeStatus::WaitingInput {
+            actions.push(SessionAction::QuickReply);
+        }
+        actions.extend([
+            SessionAction::SendText,
+            SessionAction::Queue,
+            SessionAction::Windows,
+            SessionAction::NewWindow,
             SessionAction::Rename,
-            SessionAction::Kill,
-        ];
+            if session.muted {
+                SessionAction::Unmute
+            } else {
+                SessionAction::Mute
+            },
+        ]);
+        if session.attached {
+            actions.push(SessionAction::Detach);
+        }
+        actions.push(SessionAction::Kill);
 
         self.available_actions = actions;
         self.selected_action = 0;
@@ -321,7 +813,9 @@ impl App {
     /// Start the kill confirmation flow
     pub fn start_kill(&mut self) {
         self.clear_messages();
-        if self.selected_session().is_some() {
+        if !self.marked.is_empty() {
+            self.start_bulk(BulkAction::Kill);
+        } else if self.selected_session().is_some() {
             self.pending_action = Some(SessionAction::Kill);
             self.mode = Mode::ConfirmAction;
         }
@@ -329,10 +823,10 @@ impl App {
 
     /// Confirm and execute the pending action
     pub fn confirm_action(&mut self) {
+        self.mode = Mode::Normal;
         if let Some(action) = self.pending_action.take() {
             self.execute_action(action);
         }
-        self.mode = Mode::Normal;
     }
 
     /// Execute an action on the selected session
@@ -342,6 +836,7 @@ impl App {
             return;
         };
         let session_name = session.name.clone();
+        let session_id = session.id.clone();
 
         match action {
             SessionAction::SwitchTo => {
@@ -351,6 +846,9 @@ impl App {
                 }
                 self.mode = Mode::Normal;
             }
+            SessionAction::QuickReply => self.start_quick_reply(),
+            SessionAction::SendText => self.open_send_text(),
+            SessionAction::Queue => self.start_queue(),
             SessionAction::Rename => {
                 self.mode = Mode::Rename {
                     old_name: session_name.clone(),
@@ -360,6 +858,7 @@ impl App {
             SessionAction::Kill => {
                 match Tmux::kill_session(&session_name) {
                     Ok(_) => {
+                        self.sessions.retain(|s| s.name != session_name);
                         self.refresh_sessions();
                         self.message = Some(format!("\u{f00c} Killed session '{}'", session_name));
                     }
@@ -367,6 +866,51 @@ impl App {
                 }
                 self.mode = Mode::Normal;
             }
+            SessionAction::Detach => {
+                match Tmux::detach_session(&session_id) {
+                    Ok(_) => {
+                        self.refresh_sessions();
+                        self.message = Some(format!("\u{f00c} Detached '{}'", session_name));
+                    }
+                    Err(e) => self.error = Some(format!("\u{f00d} Failed to detach: {}", e)),
+                }
+                self.mode = Mode::Normal;
+            }
+            SessionAction::Mute | SessionAction::Unmute => {
+                let muted = action == SessionAction::Mute;
+                match Tmux::set_session_muted(&session_id, muted) {
+                    Ok(_) => {
+                        if let Some(session) = self.sessions.iter_mut().find(|s| s.id == session_id)
+                        {
+                            session.muted = muted;
+                        }
+                        let verb = if muted { "Muted" } else { "Unmuted" };
+                        self.message = Some(format!(
+                            "\u{f00c} {} notifications for '{}'",
+                            verb, session_name
+                        ));
+                    }
+                    Err(e) => self.error = Some(format!("\u{f00d} {}", e)),
+                }
+                self.mode = Mode::Normal;
+            }
+            SessionAction::Windows => self.start_tree(),
+            SessionAction::NewWindow => self.start_new_window(),
+            SessionAction::KillWindow | SessionAction::KillPane => {
+                let Some(node) = self.tree_node.clone() else {
+                    return;
+                };
+                let result = match node.pane_id {
+                    Some(ref pane_id) => Tmux::kill_pane(pane_id),
+                    None => Tmux::kill_window(&node.window),
+                };
+                let message = format!("Killed '{}'", node.label);
+                self.finish_tree_action(result.map(|_| message));
+            }
+            SessionAction::RenameWindow
+            | SessionAction::MoveWindow
+            | SessionAction::BreakPane
+            | SessionAction::JoinPane => self.start_tree_action(action),
         }
     }
 
@@ -374,10 +918,14 @@ impl App {
     // Dialog flows: Rename
     // =========================================================================
 
-    /// Start the rename flow
+    /// Start the rename flow, with a name pattern when sessions are marked
     pub fn start_rename(&mut self) {
         self.clear_messages();
-        if let Some(session) = self.selected_session() {
+        if !self.marked.is_empty() {
+            self.mode = Mode::BulkRename {
+                pattern: "{name}".to_string(),
+            };
+        } else if let Some(session) = self.selected_session() {
             self.mode = Mode::Rename {
                 old_name: session.name.clone(),
                 new_name: session.name.clone(),
@@ -402,6 +950,9 @@ impl App {
 
             match Tmux::rename_session(&old, &new) {
                 Ok(_) => {
+                    if let Some(session) = self.sessions.iter_mut().find(|s| s.name == old) {
+                        session.name = new.clone();
+                    }
                     self.refresh_sessions();
                     self.message = Some(format!("\u{f00c} Renamed '{}' to '{}'", old, new));
                 }
@@ -413,6 +964,689 @@ impl App {
         self.mode = Mode::Normal;
     }
 
+    // =========================================================================
+    // Dialog flows: Quick reply
+    // =========================================================================
+
+    /// Open the quick-reply dialog for the menu the selected session's
+    /// agent is showing
+    pub fn start_quick_reply(&mut self) {
+        self.clear_messages();
+        self.mode = Mode::Normal;
+        let Some(session) = self.selected_session() else {
+            return;
+        };
+        let session_name = session.name.clone();
+        let Some(pane_id) = session.claude_code_pane.clone() else {
+            self.error = Some(format!("\u{f00d} No agent pane in '{}'", session_name));
+            return;
+        };
+
+        let menu = Tmux::capture_pane(&pane_id, QUICK_REPLY_LINES, false)
+            .ok()
+            .and_then(|content| detection::parse_menu(&content));
+        let Some(menu) = menu else {
+            self.error = Some(format!(
+                "\u{f00d} No prompt to answer in '{}'",
+                session_name
+            ));
+            return;
+        };
+
+        let selected = menu.options.iter().position(|o| o.highlighted).unwrap_or(0);
+        self.mode = Mode::QuickReply {
+            session_name,
+            pane_id,
+            menu,
+            selected,
+            typed: String::new(),
+        };
+    }
+
+    /// Move the quick-reply highlight by `delta` options, wrapping around
+    pub fn move_quick_reply(&mut self, delta: isize) {
+        if let Mode::QuickReply {
+            ref menu,
+            ref mut selected,
+            ref mut typed,
+            ..
+        } = self.mode
+        {
+            let len = menu.options.len() as isize;
+            *selected = (*selected as isize + delta).rem_euclid(len) as usize;
+            typed.clear();
+        }
+    }
+
+    /// Add a digit to the typed option number. The option is sent once no
+    /// other option's number starts with the digits; until then the
+    /// closest one is highlighted for Enter.
+    pub fn type_quick_reply(&mut self, digit: char) {
+        self.clear_messages();
+        let Mode::QuickReply {
+            ref menu,
+            ref mut selected,
+            ref mut typed,
+            ..
+        } = self.mode
+        else {
+            return;
+        };
+        typed.push(digit);
+        match menu.typed_option(typed) {
+            TypedOption::Picked(index) => {
+                *selected = index;
+                self.send_quick_reply();
+            }
+            TypedOption::Partial(index) => *selected = index,
+            TypedOption::NoMatch => {
+                self.error = Some(format!("\u{f00d} No option {} in this prompt", typed));
+                typed.clear();
+            }
+        }
+    }
+
+    /// Send the highlighted menu option to the agent pane
+    pub fn send_quick_reply(&mut self) {
+        let Mode::QuickReply {
+            ref session_name,
+            ref pane_id,
+            ref menu,
+            selected,
+            ..
+        } = self.mode
+        else {
+            return;
+        };
+        let Some(option) = menu.options.get(selected) else {
+            return;
+        };
+
+        // The agent may have moved on since the dialog opened, and the
+        // number would then answer something else or land in the prompt.
+        // This capture runs on the UI thread, like the one that opened
+        // the dialog; it is a single short tmux call.
+        let current = Tmux::capture_pane(pane_id, QUICK_REPLY_LINES, false)
+            .ok()
+            .and_then(|content| detection::parse_menu(&content))
+            .filter(|current| current.same_prompt(menu));
+        let Some(current) = current else {
+            self.error = Some(format!(
+                "\u{f00d} The prompt in '{}' changed; nothing was sent",
+                session_name
+            ));
+            self.mode = Mode::Normal;
+            return;
+        };
+        let Some(keys) = current.reply_keys(selected) else {
+            self.error = Some(format!(
+                "\u{f00d} No highlighted option in '{}'; nothing was sent",
+                session_name
+            ));
+            self.mode = Mode::Normal;
+            return;
+        };
+
+        match Tmux::send_keys(pane_id, &keys, false) {
+            Ok(_) => {
+                self.message = Some(format!(
+                    "\u{f00c} Answered '{}' in '{}'",
+                    option.label, session_name
+                ))
+            }
+            Err(e) => self.error = Some(format!("\u{f00d} Failed to reply: {}", e)),
+        }
+        self.mode = Mode::Normal;
+    }
+
+    // =========================================================================
+    // Dialog flows: Send text
+    // =========================================================================
+
+    /// Open the send-text dialog for the selected session's agent pane, or
+    /// its active pane when it has no agent. With marked sessions, the text
+    /// is broadcast to them instead.
+    pub fn start_send_text(&mut self) {
+        self.clear_messages();
+        if self.marked.is_empty() {
+            self.open_send_text();
+        } else {
+            self.start_broadcast();
+        }
+    }
+
+    /// Open the send-text dialog for the selected session
+    fn open_send_text(&mut self) {
+        let Some(session) = self.selected_session() else {
+            self.mode = Mode::Normal;
+            return;
+        };
+        self.mode = Mode::SendText {
+            to: SendTo::Session(send_target(session)),
+            text: String::new(),
+            enter: true,
+            literal: true,
+        };
+    }
+
+    /// Open the send-text dialog for a broadcast, to the marked sessions if
+    /// there are any and otherwise to the selected session's group
+    pub fn start_broadcast(&mut self) {
+        self.clear_messages();
+        if self.filtered_sessions().is_empty() {
+            return;
+        }
+        let scope = if self.marked.is_empty() {
+            BroadcastScope::Group
+        } else {
+            BroadcastScope::Marked
+        };
+        self.mode = Mode::SendText {
+            to: SendTo::Broadcast(scope),
+            text: String::new(),
+            enter: true,
+            literal: true,
+        };
+    }
+
+    /// Switch the broadcast to the next kind of target
+    pub fn cycle_broadcast_scope(&mut self) {
+        if let Mode::SendText {
+            to: SendTo::Broadcast(ref mut scope),
+            ..
+        } = self.mode
+        {
+            *scope = scope.next();
+        }
+    }
+
+    /// Sessions a broadcast with `scope` goes to, in list order
+    pub fn broadcast_targets(&self, scope: BroadcastScope) -> Vec<SendTarget> {
+        let sessions: Vec<&Session> = match scope {
+            BroadcastScope::Marked => self.marked_sessions(),
+            BroadcastScope::Group => {
+                let Some(path) = self.selected_session().map(|s| s.display_path()) else {
+                    return Vec::new();
+                };
+                self.grouped_sessions()
+                    .into_iter()
+                    .find(|(group, _)| *group == path)
+                    .map(|(_, sessions)| sessions.into_iter().map(|(_, s)| s).collect())
+                    .unwrap_or_default()
+            }
+            BroadcastScope::Filter => self.visual_sessions(),
+        };
+        sessions.into_iter().map(send_target).collect()
+    }
+
+    /// What a broadcast with `scope` targets, for the dialog title
+    pub fn broadcast_scope_label(&self, scope: BroadcastScope) -> String {
+        match scope {
+            BroadcastScope::Marked => "marked sessions".to_string(),
+            BroadcastScope::Group => match self.selected_session() {
+                Some(session) => format!("group {}", session.display_path()),
+                None => "group".to_string(),
+            },
+            BroadcastScope::Filter if self.filter.is_empty() => "all sessions".to_string(),
+            BroadcastScope::Filter => format!("sessions matching '{}'", self.filter),
+        }
+    }
+
+    /// Append typed or pasted text to the send-text input
+    pub fn push_send_text(&mut self, input: &str) {
+        if let Mode::SendText { ref mut text, .. } = self.mode {
+            // Terminals paste line breaks as \r
+            text.push_str(&input.replace("\r\n", "\n").replace('\r', "\n"));
+        }
+    }
+
+    /// Send the typed text to its session, or ask to confirm a broadcast
+    pub fn send_text(&mut self) {
+        let Mode::SendText {
+            ref to,
+            ref text,
+            enter,
+            literal,
+        } = self.mode
+        else {
+            return;
+        };
+        if text.is_empty() && !enter {
+            self.mode = Mode::Normal;
+            return;
+        }
+
+        match to {
+            SendTo::Session(to) => {
+                match Tmux::send_text(&to.target, text, enter, literal) {
+                    Ok(_) => {
+                        self.message = Some(format!(
+                            "\u{f00c} Sent {} to '{}'",
+                            describe_text(text),
+                            to.session_name
+                        ))
+                    }
+                    Err(e) => self.error = Some(format!("\u{f00d} Failed to send: {}", e)),
+                }
+                self.mode = Mode::Normal;
+            }
+            SendTo::Broadcast(scope) => {
+                let targets = self.broadcast_targets(*scope);
+                if targets.is_empty() {
+                    self.error = Some(format!(
+                        "\u{f00d} No {} to broadcast to",
+                        self.broadcast_scope_label(*scope)
+                    ));
+                    return;
+                }
+                self.mode = Mode::ConfirmBroadcast {
+                    text: text.clone(),
+                    enter,
+                    literal,
+                    targets,
+                };
+            }
+        }
+    }
+
+    /// Send a confirmed broadcast to every target and show the results
+    pub fn confirm_broadcast(&mut self) {
+        let Mode::ConfirmBroadcast {
+            ref text,
+            enter,
+            literal,
+            ref targets,
+        } = self.mode
+        else {
+            return;
+        };
+
+        let results: Vec<SessionResult> = targets
+            .iter()
+            .map(|to| SessionResult {
+                session_name: to.session_name.clone(),
+                error: Tmux::send_text(&to.target, text, enter, literal)
+                    .err()
+                    .map(|e| e.to_string()),
+            })
+            .collect();
+
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        if failed == 0 {
+            self.message = Some(format!(
+                "\u{f00c} Sent {} to {} sessions",
+                describe_text(text),
+                results.len()
+            ));
+        } else {
+            self.error = Some(format!(
+                "\u{f00d} Failed to send to {} of {} sessions",
+                failed,
+                results.len()
+            ));
+        }
+        self.mode = Mode::BulkResults {
+            title: "\u{f1d8} Sent to".to_string(),
+            results,
+        };
+    }
+
+    // =========================================================================
+    // Marking and bulk actions
+    // =========================================================================
+
+    /// Whether a session is marked
+    pub fn is_marked(&self, session: &Session) -> bool {
+        self.marked.contains(&session.id)
+    }
+
+    /// Marked sessions in the order they are shown, including those hidden
+    /// by the filter
+    pub fn marked_sessions(&self) -> Vec<&Session> {
+        let mut sessions: Vec<&Session> = self
+            .visual_sessions()
+            .into_iter()
+            .filter(|s| self.is_marked(s))
+            .collect();
+        let hidden: Vec<&Session> = self
+            .sessions
+            .iter()
+            .filter(|s| self.is_marked(s) && !sessions.iter().any(|m| m.id == s.id))
+            .collect();
+        sessions.extend(hidden);
+        sessions
+    }
+
+    /// Mark or unmark the selected session
+    pub fn toggle_mark(&mut self) {
+        let Some(id) = self.selected_session().map(|s| s.id.clone()) else {
+            return;
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+    }
+
+    /// Mark every session in the selected session's group
+    pub fn mark_group(&mut self) {
+        let Some(path) = self.selected_session().map(|s| s.display_path()) else {
+            return;
+        };
+        let ids: Vec<String> = self
+            .grouped_sessions()
+            .into_iter()
+            .filter(|(group, _)| *group == path)
+            .flat_map(|(_, sessions)| sessions.into_iter().map(|(_, s)| s.id.clone()))
+            .collect();
+        self.marked.extend(ids);
+    }
+
+    /// Mark every session the filter shows
+    pub fn mark_filtered(&mut self) {
+        let ids: Vec<String> = self
+            .filtered_sessions()
+            .iter()
+            .map(|s| s.id.clone())
+            .collect();
+        self.marked.extend(ids);
+    }
+
+    /// Unmark all sessions
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+    }
+
+    /// Ask to confirm `action` on every marked session
+    fn start_bulk(&mut self, action: BulkAction) {
+        let targets: Vec<BulkTarget> = self
+            .marked_sessions()
+            .into_iter()
+            // Only attached sessions have clients to detach
+            .filter(|s| action != BulkAction::Detach || s.attached)
+            .map(|s| BulkTarget {
+                session_id: s.id.clone(),
+                session_name: s.name.clone(),
+                new_name: None,
+            })
+            .collect();
+        if targets.is_empty() {
+            self.error = Some(if action == BulkAction::Detach {
+                "\u{f00d} No marked session is attached".to_string()
+            } else {
+                "\u{f00d} No marked sessions".to_string()
+            });
+            return;
+        }
+        self.mode = Mode::ConfirmBulk { action, targets };
+    }
+
+    /// Detach the marked sessions, or the selected one
+    pub fn start_detach(&mut self) {
+        self.clear_messages();
+        if !self.marked.is_empty() {
+            self.start_bulk(BulkAction::Detach);
+        } else if self.selected_session().is_some_and(|s| s.attached) {
+            self.execute_action(SessionAction::Detach);
+        }
+    }
+
+    /// Renames the pattern in `BulkRename` mode gives the marked sessions,
+    /// or why it can't be used
+    pub fn bulk_rename_targets(&self, pattern: &str) -> Result<Vec<BulkTarget>, String> {
+        let marked = self.marked_sessions();
+        let targets: Vec<BulkTarget> = marked
+            .iter()
+            .enumerate()
+            .map(|(i, s)| BulkTarget {
+                session_id: s.id.clone(),
+                session_name: s.name.clone(),
+                new_name: Some(apply_rename_pattern(pattern, &s.name, i + 1)),
+            })
+            .collect();
+
+        let existing = self
+            .sessions
+            .iter()
+            .map(|s| (s.id.as_str(), s.name.as_str()));
+        check_rename_targets(&targets, existing)?;
+        Ok(targets)
+    }
+
+    /// Ask to confirm the renames of the typed pattern
+    pub fn confirm_bulk_rename(&mut self) {
+        let Mode::BulkRename { ref pattern } = self.mode else {
+            return;
+        };
+        match self.bulk_rename_targets(pattern) {
+            Ok(targets) => {
+                self.mode = Mode::ConfirmBulk {
+                    action: BulkAction::Rename,
+                    targets,
+                }
+            }
+            Err(e) => self.error = Some(format!("\u{f00d} {}", e)),
+        }
+    }
+
+    /// Run a confirmed bulk action and show the results
+    pub fn confirm_bulk(&mut self) {
+        let Mode::ConfirmBulk {
+            action,
+            ref targets,
+        } = self.mode
+        else {
+            return;
+        };
+
+        // Killing the current session ends the tmux client, so do it last
+        let mut targets = targets.clone();
+        let current = self.current_session.clone();
+        targets.sort_by_key(|t| Some(&t.session_name) == current.as_ref());
+
+        let results: Vec<SessionResult> = targets
+            .iter()
+            .map(|target| {
+                let result = match action {
+                    BulkAction::Kill => Tmux::kill_session(&target.session_id),
+                    BulkAction::Detach => Tmux::detach_session(&target.session_id),
+                    BulkAction::Rename => Tmux::rename_session(
+                        &target.session_id,
+                        target.new_name.as_deref().unwrap_or_default(),
+                    ),
+                };
+                SessionResult {
+                    session_name: match target.new_name {
+                        Some(ref new_name) => format!("{} → {}", target.session_name, new_name),
+                        None => target.session_name.clone(),
+                    },
+                    error: result.err().map(|e| e.to_string()),
+                }
+            })
+            .collect();
+
+        for (target, result) in targets.iter().zip(&results) {
+            if result.error.is_some() {
+                continue;
+            }
+            match action {
+                BulkAction::Kill => {
+                    self.sessions.retain(|s| s.id != target.session_id);
+                    self.marked.remove(&target.session_id);
+                }
+                BulkAction::Rename => {
+                    if let Some(session) =
+                        self.sessions.iter_mut().find(|s| s.id == target.session_id)
+                    {
+                        session.name = target.new_name.clone().unwrap_or_default();
+                    }
+                }
+                BulkAction::Detach => {}
+            }
+        }
+        self.refresh_sessions();
+
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        if failed == 0 {
+            self.message = Some(format!(
+                "\u{f00c} {} {} sessions",
+                action.done(),
+                results.len()
+            ));
+        } else {
+            self.error = Some(format!(
+                "\u{f00d} {} failed for {} of {} sessions",
+                action.verb(),
+                failed,
+                results.len()
+            ));
+        }
+        self.mode = Mode::BulkResults {
+            title: action.done().to_string(),
+            results,
+        };
+    }
+
+    // =========================================================================
+    // Dialog flows: Prompt queue
+    // =========================================================================
+
+    /// Open the prompt queue of the selected session
+    pub fn start_queue(&mut self) {
+        self.clear_messages();
+        let Some(session) = self.selected_session() else {
+            self.mode = Mode::Normal;
+            return;
+        };
+        self.mode = Mode::Queue {
+            key: queue::key(session),
+            session_name: session.name.clone(),
+            selected: 0,
+            input: None,
+        };
+        self.queues = queue::load();
+    }
+
+    /// Prompts queued under a queue key
+    pub fn queued_prompts(&self, key: &str) -> &[String] {
+        self.queues
+            .get(key)
+            .map_or(&[], |queue| queue.prompts.as_slice())
+    }
+
+    /// Move the queue highlight by `delta` prompts
+    pub fn move_queue_selection(&mut self, delta: isize) {
+        let Mode::Queue {
+            ref key, selected, ..
+        } = self.mode
+        else {
+            return;
+        };
+        let len = self.queued_prompts(key).len();
+        let selected_new = (selected as isize + delta).clamp(0, len.saturating_sub(1) as isize);
+        if let Mode::Queue {
+            ref mut selected, ..
+        } = self.mode
+        {
+            *selected = selected_new as usize;
+        }
+    }
+
+    /// Change the open queue on disk and keep the highlight on `selected`
+    fn edit_queue(&mut self, f: impl FnOnce(&mut Queues, &str, usize) -> usize) {
+        let Mode::Queue {
+            ref key, selected, ..
+        } = self.mode
+        else {
+            return;
+        };
+        let key = key.clone();
+        match queue::update(|queues| f(queues, &key, selected)) {
+            Ok((queues, selected_new)) => {
+                self.queues = queues;
+                if let Mode::Queue {
+                    ref mut selected, ..
+                } = self.mode
+                {
+                    *selected = selected_new;
+                }
+                self.move_queue_selection(0);
+            }
+            Err(e) => self.error = Some(format!("\u{f00d} Failed to save the queue: {:#}", e)),
+        }
+    }
+
+    /// Move the highlighted prompt up or down
+    pub fn reorder_queued(&mut self, down: bool) {
+        self.edit_queue(|queues, key, selected| queues.move_prompt(key, selected, down));
+    }
+
+    /// Delete the highlighted prompt
+    pub fn delete_queued(&mut self) {
+        self.edit_queue(|queues, key, selected| {
+            queues.remove(key, selected);
+            selected
+        });
+    }
+
+    /// Pause or resume the open queue
+    pub fn toggle_queue_paused(&mut self) {
+        self.edit_queue(|queues, key, selected| {
+            queues.toggle_paused(key);
+            selected
+        });
+    }
+
+    /// Start typing a new prompt
+    pub fn start_queue_input(&mut self) {
+        if let Mode::Queue { ref mut input, .. } = self.mode {
+            *input = Some(String::new());
+        }
+    }
+
+    /// Stop typing a new prompt without adding it
+    pub fn cancel_queue_input(&mut self) {
+        if let Mode::Queue { ref mut input, .. } = self.mode {
+            *input = None;
+        }
+    }
+
+    /// Append typed or pasted text to the new prompt
+    pub fn push_queue_input(&mut self, text: &str) {
+        if let Mode::Queue {
+            input: Some(ref mut input),
+            ..
+        } = self.mode
+        {
+            input.push_str(&text.replace("\r\n", "\n").replace('\r', "\n"));
+        }
+    }
+
+    /// Add the typed prompt at the end of the queue
+    pub fn add_queued(&mut self) {
+        let Mode::Queue { ref mut input, .. } = self.mode else {
+            return;
+        };
+        let Some(prompt) = input.take() else {
+            return;
+        };
+        if prompt.trim().is_empty() {
+            return;
+        }
+        // A prompt queued while the agent works goes out when it finishes,
+        // even if no refresh saw it working before that
+        let working = self.sessions.iter().any(|s| {
+            s.claude_code_status == ClaudeCodeStatus::Working
+                && matches!(self.mode, Mode::Queue { ref key, .. } if *key == queue::key(s))
+        });
+        self.edit_queue(|queues, key, _| {
+            queues.push(key, prompt);
+            if working {
+                queues.mark_working(key);
+            }
+            queues.get(key).map_or(0, |queue| queue.prompts.len() - 1)
+        });
+    }
+
     // =========================================================================
     // Dialog flows: New Session
     // =========================================================================
@@ -483,6 +1717,67 @@ impl App {
         self.mode = Mode::Normal;
     }
 
+    // =========================================================================
+    // Preview mode
+    // =========================================================================
+
+    /// Read the whole history of the selected session's preview pane, or of
+    /// the pane highlighted in the tree
+    pub fn open_pager(&mut self) {
+        self.clear_messages();
+        let target = match self.selected_tree_node() {
+            Some((session, TreeNode::Pane(pane))) => Some((
+                pane.id.clone(),
+                format!("{}:{}.{}", session.name, pane.window_index, pane.id),
+            )),
+            Some((_, TreeNode::Window { .. })) => None,
+            None => self.selected_session().and_then(|session| {
+                let pane_id = session.preview_pane()?;
+                Some((pane_id.to_string(), format!("{}:{}", session.name, pane_id)))
+            }),
+        };
+        let Some((pane_id, title)) = target else {
+            self.error = Some("\u{f00d} Select a pane first".to_string());
+            return;
+        };
+
+        match Tmux::capture_history(&pane_id) {
+            Ok(content) => {
+                self.pager = Some(Pager::new(title, &content));
+                // Closing goes back to the tree when opened from it
+                self.tree_node = self.node_ref();
+                self.mode = Mode::Preview { search: None };
+            }
+            Err(e) => self.error = Some(format!("\u{f00d} {}", e)),
+        }
+    }
+
+    /// Close the pager
+    pub fn close_pager(&mut self) {
+        self.pager = None;
+        self.cancel();
+    }
+
+    /// Start typing a search
+    pub fn start_pager_search(&mut self) {
+        self.mode = Mode::Preview {
+            search: Some(String::new()),
+        };
+    }
+
+    /// Search for the typed text; an empty search clears the highlights
+    pub fn confirm_pager_search(&mut self) {
+        if let Mode::Preview {
+            search: Some(ref query),
+        } = self.mode
+        {
+            if let Some(ref mut pager) = self.pager {
+                pager.search(query);
+            }
+        }
+        self.mode = Mode::Preview { search: None };
+    }
+
     // =========================================================================
     // Filter mode
     // =========================================================================
@@ -532,31 +1827,22 @@ impl App {
     /// Cancel current mode and return to normal
     pub fn cancel(&mut self) {
         self.pending_action = None;
-        self.mode = Mode::Normal;
+        // Dialogs opened from the tree go back to it
+        self.mode = match self.tree_node.take() {
+            Some(node) if !matches!(self.mode, Mode::Tree { .. }) => Mode::Tree {
+                selected: node.position,
+            },
+            _ => Mode::Normal,
+        };
     }
 
     // =========================================================================
     // Status and statistics
     // =========================================================================
 
-    /// Count sessions by status
-    pub fn status_counts(&self) -> (usize, usize, usize) {
-        use crate::session::ClaudeCodeStatus;
-
-        let mut working = 0;
-        let mut waiting = 0;
-        let mut idle = 0;
-
-        for session in &self.sessions {
-            match session.claude_code_status {
-                ClaudeCodeStatus::Working => working += 1,
-                ClaudeCodeStatus::WaitingInput => waiting += 1,
-                ClaudeCodeStatus::Idle => idle += 1,
-                ClaudeCodeStatus::Unknown => {}
-            }
-        }
-
-        (working, waiting, idle)
+    /// Count agent panes by status
+    pub fn status_counts(&self) -> StatusCounts {
+        StatusCounts::of(&self.sessions)
     }
 
     // =========================================================================
@@ -690,15 +1976,40 @@ impl App {
 
                 index += 1; // selected session row itself
                 index += 1; // metadata row
+                index += self.expanded_tree_rows();
                 index += 1; // separator
                 index += self.selected_action;
 
                 index
             }
+            Mode::Tree { .. } => {
+                let selected = self.tree_selection().unwrap_or(0);
+                let mut index = self.selected + group_offset;
+
+                index += 1; // selected session row itself
+                index += 1; // metadata row
+                if let Some(session) = self.selected_session() {
+                    index += session
+                        .tree()
+                        .iter()
+                        .take(selected)
+                        .map(tree_node_rows)
+                        .sum::<usize>();
+                }
+
+                index
+            }
             _ => self.selected + group_offset,
         }
     }
 
+    /// Number of agent pane rows, including question lines, in the
+    /// expanded session view
+    pub fn expanded_tree_rows(&self) -> usize {
+        self.selected_session()
+            .map_or(0, |session| session.tree().iter().map(tree_node_rows).sum())
+    }
+
     /// Compute the total number of items in the rendered list.
     pub fn compute_total_list_items(&self) -> usize {
         let filtered_count = self.filtered_sessions().len();
@@ -713,13 +2024,52 @@ impl App {
                 let mut total = filtered_count + group_headers;
 
                 total += 1; // metadata row
+                total += self.expanded_tree_rows();
                 total += 1; // separator
                 total += self.available_actions.len();
                 total += 1; // end separator
 
                 total
             }
+            Mode::Tree { .. } => {
+                let mut total = filtered_count + group_headers;
+
+                total += 1; // metadata row
+                total += self.expanded_tree_rows();
+                total += 1; // end separator
+
+                total
+            }
             _ => filtered_count + group_headers,
         }
     }
 }
+
+/// A session's agent pane, or its active pane when it has no agent
+fn send_target(session: &Session) -> SendTarget {
+    SendTarget {
+        session_name: session.name.clone(),
+        target: session
+            .claude_code_pane
+            .clone()
+            .unwrap_or_else(|| session.id.clone()),
+    }
+}
+
+/// Short description of sent text for status messages
+fn describe_text(text: &str) -> String {
+    match text.lines().count() {
+        0 => "Enter".to_string(),
+        1 => format!("'{}'", text),
+        lines => format!("{} lines", lines),
+    }
+}
+
+/// Rows a tree node takes in the expanded view: waiting panes also show
+/// their question
+fn tree_node_rows(node: &TreeNode) -> usize {
+    match node {
+        TreeNode::Window { .. } => 1,
+        TreeNode::Pane(pane) => 1 + pane.question.as_ref().map_or(0, |q| q.text.lines().count()),
+    }
+}
@@ -1,5 +1,7 @@
 //! Application mode and action types
 
+use crate::detection::Menu;
+
 /// The current mode/state of the application
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Mode {
@@ -25,19 +27,230 @@ pub enum Mode {
     },
     /// Renaming a session
     Rename { old_name: String, new_name: String },
+    /// Answering an agent's numbered menu
+    QuickReply {
+        session_name: String,
+        pane_id: String,
+        menu: Menu,
+        /// Index of the highlighted option
+        selected: usize,
+        /// Digits typed toward a two-digit option number
+        typed: String,
+    },
+    /// Typing text to send to one or more sessions
+    SendText {
+        to: SendTo,
+        /// Text typed so far, may span several lines
+        text: String,
+        /// Press Enter after the text
+        enter: bool,
+        /// Send the text as-is instead of as tmux key names like `C-c`
+        literal: bool,
+    },
+    /// Confirming a broadcast to the listed sessions
+    ConfirmBroadcast {
+        text: String,
+        enter: bool,
+        literal: bool,
+        targets: Vec<SendTarget>,
+    },
+    /// Typing a name pattern to rename the marked sessions
+    BulkRename { pattern: String },
+    /// Confirming an action on the listed sessions
+    ConfirmBulk {
+        action: BulkAction,
+        targets: Vec<BulkTarget>,
+    },
+    /// Showing how a broadcast or bulk action went for each session
+    BulkResults {
+        title: String,
+        results: Vec<SessionResult>,
+    },
+    /// Editing a session's prompt queue
+    Queue {
+        /// The session's key in the queues, see [`crate::queue::key`]
+        key: String,
+        session_name: String,
+        /// Index of the highlighted prompt
+        selected: usize,
+        /// Prompt being typed, while adding one
+        input: Option<String>,
+    },
+    /// Browsing the windows and panes of the selected session
+    Tree {
+        /// Index into [`Session::tree`](crate::session::Session::tree)
+        selected: usize,
+    },
+    /// Creating a window in the selected session
+    NewWindow {
+        /// Window name; empty lets tmux name it after the command
+        name: String,
+        /// Whether to start Claude Code in the new window
+        start_claude: bool,
+    },
+    /// Renaming the window picked in the tree
+    RenameWindow { new_name: String },
+    /// Choosing the session a window or pane moves to
+    PickSession {
+        action: SessionAction,
+        destinations: Vec<Destination>,
+        /// Index of the highlighted session
+        selected: usize,
+    },
+    /// Reading a pane's history in the pager
+    Preview {
+        /// Search being typed after `/`
+        search: Option<String>,
+    },
     /// Showing help
     Help,
 }
 
+/// A window or pane picked in the tree for an action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRef {
+    /// Position in the tree, to return to afterwards
+    pub position: usize,
+    pub session_id: String,
+    /// tmux target of the window, e.g. `$1:2`
+    pub window: String,
+    pub window_name: String,
+    /// ID of the picked pane; `None` when a window is picked
+    pub pane_id: Option<String>,
+    /// Whether the session has nothing else, so moving or killing the node
+    /// ends it
+    pub last_in_session: bool,
+    /// How the node is named in messages, e.g. `work:2` or `work:2.%5`
+    pub label: String,
+}
+
+/// A session a window or pane can move to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Destination {
+    pub session_id: String,
+    pub session_name: String,
+}
+
+/// Where typed text goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendTo {
+    /// One session, sent right away
+    Session(SendTarget),
+    /// Several sessions, sent after a confirmation
+    Broadcast(BroadcastScope),
+}
+
+/// A session and the pane in it that receives text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendTarget {
+    pub session_name: String,
+    /// Agent pane, or the session ID for its active pane
+    pub target: String,
+}
+
+/// Outcome of a broadcast or bulk action for one session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionResult {
+    pub session_name: String,
+    pub error: Option<String>,
+}
+
+/// An action run on every marked session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Kill,
+    Rename,
+    Detach,
+}
+
+impl BulkAction {
+    /// Verb for the confirmation and result dialogs
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Kill => "Kill",
+            Self::Rename => "Rename",
+            Self::Detach => "Detach",
+        }
+    }
+
+    /// Past tense of [`BulkAction::verb`] for the results
+    pub fn done(&self) -> &'static str {
+        match self {
+            Self::Kill => "Killed",
+            Self::Rename => "Renamed",
+            Self::Detach => "Detached",
+        }
+    }
+}
+
+/// A session affected by a bulk action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkTarget {
+    pub session_id: String,
+    pub session_name: String,
+    /// Name after a bulk rename
+    pub new_name: Option<String>,
+}
+
+/// Which sessions a broadcast goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BroadcastScope {
+    /// Sessions marked with `m`
+    Marked,
+    /// The selected session's directory group
+    Group,
+    /// Every session shown with the current filter
+    Filter,
+}
+
+impl BroadcastScope {
+    /// The scope after this one when cycling with Tab
+    pub fn next(self) -> Self {
+        match self {
+            Self::Marked => Self::Group,
+            Self::Group => Self::Filter,
+            Self::Filter => Self::Marked,
+        }
+    }
+}
+
 /// An action that can be performed on a session
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SessionAction {
     /// Switch to this session
     SwitchTo,
+    /// Answer the prompt the agent is waiting on
+    QuickReply,
+    /// Type text to send to the agent
+    SendText,
+    /// Edit the prompts sent when the agent goes idle
+    Queue,
     /// Rename this session
     Rename,
     /// Kill this session
     Kill,
+    /// Detach the clients attached to this session
+    Detach,
+    /// Stop notifications for this session
+    Mute,
+    /// Resume notifications for this session
+    Unmute,
+    /// Browse this session's windows and panes
+    Windows,
+    /// Create a window in this session
+    NewWindow,
+    /// Kill the window picked in the tree
+    KillWindow,
+    /// Kill the pane picked in the tree
+    KillPane,
+    /// Rename the window picked in the tree
+    RenameWindow,
+    /// Move the window picked in the tree to another session
+    MoveWindow,
+    /// Move the pane picked in the tree into a new window of a session
+    BreakPane,
+    /// Move the pane picked in the tree into another session's current window
+    JoinPane,
 }
 
 impl SessionAction {
@@ -45,14 +258,56 @@ impl SessionAction {
     pub fn label(&self) -> &'static str {
         match self {
             Self::SwitchTo => "\u{f064} Switch to session",
+            Self::QuickReply => "\u{f075} Reply to prompt",
+            Self::SendText => "\u{f1d8} Send text",
+            Self::Queue => "\u{f0cb} Prompt queue",
             Self::Rename => "\u{f040} Rename session",
             Self::Kill => "\u{f00d} Kill session",
+            Self::Detach => "\u{f127} Detach clients",
+            Self::Mute => "\u{f1f6} Mute notifications",
+            Self::Unmute => "\u{f0f3} Unmute notifications",
+            Self::Windows => "\u{f2d0} Windows and panes",
+            Self::NewWindow => "\u{f067} New window",
+            Self::KillWindow => "\u{f00d} Kill window",
+            Self::KillPane => "\u{f00d} Kill pane",
+            Self::RenameWindow => "\u{f040} Rename window",
+            Self::MoveWindow => "\u{f0ec} Move window",
+            Self::BreakPane => "\u{f2d0} Break pane",
+            Self::JoinPane => "\u{f0db} Join pane",
         }
     }
 
     /// Whether this action requires confirmation
     pub fn requires_confirmation(&self) -> bool {
-        matches!(self, Self::Kill)
+        matches!(self, Self::Kill | Self::KillWindow | Self::KillPane)
+    }
+}
+
+/// Order of sessions in the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// Oldest session first
+    #[default]
+    Created,
+    /// Sessions waiting for input longest first, then the rest by creation
+    WaitingLongest,
+}
+
+impl SortMode {
+    /// The mode after this one when cycling with `o`
+    pub fn next(self) -> Self {
+        match self {
+            Self::Created => Self::WaitingLongest,
+            Self::WaitingLongest => Self::Created,
+        }
+    }
+
+    /// Short name for the status bar
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::WaitingLongest => "waiting longest",
+        }
     }
 }
 
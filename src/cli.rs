@@ -0,0 +1,568 @@
+//! Non-interactive command-line interface
+//!
+//! Subcommands run a single tmux operation and print the result without
+//! touching the terminal. With no subcommand, `main` starts the TUI.
+
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use crate::agents::{Agent, AgentDetector};
+use crate::app::App;
+use crate::config::Config;
+use crate::daemon;
+use crate::detection::{first_match, strip_ansi};
+use crate::discovery::{capture_status_lines, Discovery};
+use crate::hooks::{self, HookEvent};
+use crate::mcp;
+use crate::output::{self, OutputFormat};
+use crate::status_line;
+use crate::tmux::Tmux;
+use crate::watch;
+
+const USAGE: &str = "\
+Usage: tsm [COMMAND]
+
+Commands:
+  list [--format FORMAT]       List sessions with their Claude Code status
+                               FORMAT: table (default), json, ndjson, tsv
+  switch <name>                Switch to (or attach) a session
+  new [name] [-c DIR] [--shell]
+                               Create a detached session (starts Claude unless --shell)
+  kill <name>                  Kill a session
+  rename <old> <new>           Rename a session
+  detect [--pane ID] [--agent NAME] [--explain]
+                               Detect agent status of a pane (or stdin) and
+                               with --explain show which rule matched
+  hook <event>                 Record Claude Code hook state for $TMUX_PANE
+  hook install [--settings FILE]
+                               Add tsm hooks to Claude Code's settings.json
+  daemon                       Track status of all sessions in the background
+  status-line [--template TEMPLATE]
+                               Print agent status counts for tmux's status-right
+  watch [--interval SECS]      Print session events as NDJSON
+  mcp                          Serve tmux session tools over MCP (stdio)
+  help                         Show this help
+
+Run without a command to open the interactive session manager.";
+
+/// A parsed subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    List {
+        format: OutputFormat,
+    },
+    Switch {
+        name: String,
+    },
+    New {
+        name: Option<String>,
+        path: Option<PathBuf>,
+        start_claude: bool,
+    },
+    Kill {
+        name: String,
+    },
+    Rename {
+        old_name: String,
+        new_name: String,
+    },
+    Detect {
+        pane: Option<String>,
+        agent: Option<Agent>,
+        explain: bool,
+    },
+    Hook {
+        event: HookEvent,
+    },
+    HookInstall {
+        settings: Option<PathBuf>,
+    },
+    Daemon,
+    Mcp,
+    StatusLine {
+        template: Option<String>,
+    },
+    Watch {
+        interval: Duration,
+    },
+    Help,
+}
+
+/// Parse command-line arguments (without the program name).
+/// Returns `None` when no subcommand was given and the TUI should start.
+pub fn parse(args: &[String]) -> Result<Option<Command>> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    let command = match command.as_str() {
+        "list" | "ls" => parse_list(rest)?,
+        "switch" | "attach" => {
+            let args = expect_positional(rest, 1, "switch <name>")?;
+            Command::Switch {
+                name: args[0].clone(),
+            }
+        }
+        "new" => parse_new(rest)?,
+        "kill" => {
+            let args = expect_positional(rest, 1, "kill <name>")?;
+            Command::Kill {
+                name: args[0].clone(),
+            }
+        }
+        "rename" => {
+            let args = expect_positional(rest, 2, "rename <old> <new>")?;
+            Command::Rename {
+                old_name: args[0].clone(),
+                new_name: args[1].clone(),
+            }
+        }
+        "detect" => parse_detect(rest)?,
+        "hook" => parse_hook(rest)?,
+        "daemon" => {
+            expect_positional(rest, 0, "daemon")?;
+            Command::Daemon
+        }
+        "status-line" => parse_status_line(rest)?,
+        "watch" => parse_watch(rest)?,
+        "mcp" => {
+            expect_positional(rest, 0, "mcp")?;
+            Command::Mcp
+        }
+        "help" | "-h" | "--help" => Command::Help,
+        other => bail!("unknown command '{}'\n\n{}", other, USAGE),
+    };
+
+    Ok(Some(command))
+}
+
+/// Parse `list [--format FORMAT]`
+fn parse_list(args: &[String]) -> Result<Command> {
+    let mut format = OutputFormat::default();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-f" | "--format" => match iter.next() {
+                Some(value) => format = value.parse()?,
+                None => bail!("{} requires a value", arg),
+            },
+            flag => match flag.strip_prefix("--format=") {
+                Some(value) => format = value.parse()?,
+                None => bail!("unexpected argument '{}' for list", flag),
+            },
+        }
+    }
+
+    Ok(Command::List { format })
+}
+
+/// Parse `new [name] [-c DIR] [--shell]`
+fn parse_new(args: &[String]) -> Result<Command> {
+    let mut name = None;
+    let mut path = None;
+    let mut start_claude = true;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-c" | "--path" => match iter.next() {
+                Some(dir) => path = Some(PathBuf::from(dir)),
+                None => bail!("{} requires a directory", arg),
+            },
+            "--shell" => start_claude = false,
+            "--claude" => start_claude = true,
+            flag if flag.starts_with('-') => bail!("unknown option '{}' for new", flag),
+            value if name.is_none() => name = Some(value.to_string()),
+            value => bail!("unexpected argument '{}' for new", value),
+        }
+    }
+
+    Ok(Command::New {
+        name,
+        path,
+        start_claude,
+    })
+}
+
+/// Parse `detect [--pane ID] [--agent NAME] [--explain]`
+fn parse_detect(args: &[String]) -> Result<Command> {
+    let mut pane = None;
+    let mut agent = None;
+    let mut explain = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-t" | "--pane" => match iter.next() {
+                Some(id) => pane = Some(id.clone()),
+                None => bail!("{} requires a pane id", arg),
+            },
+            "-a" | "--agent" => match iter.next() {
+                Some(name) => agent = Some(name.parse()?),
+                None => bail!("{} requires an agent name", arg),
+            },
+            "--explain" => explain = true,
+            other => bail!("unexpected argument '{}' for detect", other),
+        }
+    }
+
+    Ok(Command::Detect {
+        pane,
+        agent,
+        explain,
+    })
+}
+
+/// Parse `hook <event>` or `hook install [--settings FILE]`
+fn parse_hook(args: &[String]) -> Result<Command> {
+    match args {
+        [sub, rest @ ..] if sub == "install" => match rest {
+            [] => Ok(Command::HookInstall { settings: None }),
+            [flag, file] if flag == "--settings" => Ok(Command::HookInstall {
+                settings: Some(PathBuf::from(file)),
+            }),
+            _ => bail!("usage: tsm hook install [--settings FILE]"),
+        },
+        [event] => Ok(Command::Hook {
+            event: event.parse()?,
+        }),
+        _ => bail!("usage: tsm hook <event>"),
+    }
+}
+
+/// Parse `status-line [--template TEMPLATE]`
+fn parse_status_line(args: &[String]) -> Result<Command> {
+    match args {
+        [] => Ok(Command::StatusLine { template: None }),
+        [flag, template] if flag == "-t" || flag == "--template" => Ok(Command::StatusLine {
+            template: Some(template.clone()),
+        }),
+        _ => bail!("usage: tsm status-line [--template TEMPLATE]"),
+    }
+}
+
+/// Parse `watch [--interval SECS]`
+fn parse_watch(args: &[String]) -> Result<Command> {
+    let interval = match args {
+        [] => watch::DEFAULT_INTERVAL,
+        [flag, secs] if flag == "-n" || flag == "--interval" => {
+            // try_from rejects values too large for a Duration, like inf
+            match secs.parse().map(Duration::try_from_secs_f64) {
+                Ok(Ok(interval)) if !interval.is_zero() => interval,
+                _ => bail!("invalid interval '{}'", secs),
+            }
+        }
+        _ => bail!("usage: tsm watch [--interval SECS]"),
+    };
+    Ok(Command::Watch { interval })
+}
+
+/// Check that exactly `count` positional arguments were given
+fn expect_positional<'a>(args: &'a [String], count: usize, usage: &str) -> Result<&'a [String]> {
+    if args.len() != count {
+        bail!("usage: tsm {}", usage);
+    }
+    Ok(args)
+}
+
+/// Run a subcommand, printing its result to stdout
+pub fn run(command: Command) -> Result<()> {
+    match command {
+        Command::List { format } => {
+            let sessions = Tmux::list_sessions()?;
+            print!("{}", output::render_sessions(&sessions, format)?);
+            Ok(())
+        }
+        Command::Switch { name } => Tmux::switch_to_session(&Tmux::session_target(&name)),
+        Command::New {
+            name,
+            path,
+            start_claude,
+        } => {
+            let name = name.unwrap_or_else(|| App::generate_session_name(start_claude));
+            let path = match path {
+                Some(path) => path,
+                None => std::env::current_dir()?,
+            };
+            Tmux::new_session(&name, &path, start_claude)?;
+            println!("{}", name);
+            Ok(())
+        }
+        Command::Kill { name } => {
+            Tmux::kill_session(&Tmux::session_target(&name))?;
+            println!("Killed session '{}'", name);
+            Ok(())
+        }
+        Command::Rename { old_name, new_name } => {
+            Tmux::rename_session(&Tmux::session_target(&old_name), &new_name)?;
+            println!("Renamed '{}' to '{}'", old_name, new_name);
+            Ok(())
+        }
+        Command::Detect {
+            pane,
+            agent,
+            explain,
+        } => run_detect(pane, agent, explain),
+        Command::Hook { event } => {
+            // A failing hook would show up as an error inside Claude Code,
+            // so problems are only reported on stderr
+            if let Err(e) = hooks::run(event) {
+                eprintln!("tsm hook: {:#}", e);
+            }
+            Ok(())
+        }
+        Command::HookInstall { settings } => run_hook_install(settings),
+        Command::Daemon => daemon::run(),
+        Command::Mcp => mcp::run(),
+        Command::StatusLine { template } => status_line::run(template),
+        Command::Watch { interval } => watch::run(interval),
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    }
+}
+
+/// Detect the status of a pane, or of content read from stdin
+fn run_detect(pane: Option<String>, agent: Option<Agent>, explain: bool) -> Result<()> {
+    let mut discovery = Discovery::new(&Config::load()?)?;
+
+    let content = match pane {
+        Some(ref pane_id) => capture_status_lines(pane_id)?,
+        None => {
+            let mut content = String::new();
+            std::io::stdin().read_to_string(&mut content)?;
+            content
+        }
+    };
+
+    // Without --agent, use whatever agent discovery found in the pane
+    let agent = match (agent, &pane) {
+        (Some(agent), _) => agent,
+        (None, Some(pane_id)) => discovery
+            .list_sessions()?
+            .into_iter()
+            .find(|s| s.claude_code_pane.as_deref() == Some(pane_id.as_str()))
+            .and_then(|s| s.agent)
+            .unwrap_or(Agent::Claude),
+        (None, None) => Agent::Claude,
+    };
+
+    let Some(detector) = discovery.detector(agent) else {
+        bail!("no detector for {}", agent.label());
+    };
+
+    if explain {
+        print!("{}", explain_detection(detector, &content));
+    } else {
+        println!("{}", detector.detect_status(&content).label());
+    }
+    Ok(())
+}
+
+/// Add tsm's hooks to Claude Code's settings
+fn run_hook_install(settings: Option<PathBuf>) -> Result<()> {
+    let Some(settings) = settings.or_else(hooks::settings_path) else {
+        bail!("Could not determine the Claude Code settings path");
+    };
+    let program = std::env::current_exe()?;
+
+    let added = hooks::install(&settings, &program.to_string_lossy())?;
+    if added.is_empty() {
+        println!("Hooks already installed in {}", settings.display());
+    } else {
+        let names: Vec<&str> = added.iter().map(|event| event.name()).collect();
+        println!(
+            "Added hooks for {} to {}",
+            names.join(", "),
+            settings.display()
+        );
+    }
+    Ok(())
+}
+
+/// Describe each rule in the order it is checked and which one decided
+/// the status
+fn explain_detection(detector: &dyn AgentDetector, content: &str) -> String {
+    let stripped = strip_ansi(content);
+    let matched = first_match(detector.rules(), &stripped);
+
+    let mut out = format!("Agent: {}\n\nCaptured content:\n", detector.agent().label());
+    for line in stripped.lines() {
+        out.push_str(&format!("  │ {}\n", line));
+    }
+
+    out.push_str("\nRules (in priority order):\n");
+    if detector.rules().is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for rule in detector.rules() {
+        let marker = if rule.matches(&stripped) { "✓" } else { " " };
+        out.push_str(&format!(
+            "  {} {:>4}  {:<7}  {:<16} {}\n",
+            marker,
+            rule.priority,
+            rule.status.label(),
+            rule.name,
+            rule.describe()
+        ));
+    }
+
+    let status = detector.detect_status(content);
+    match matched {
+        Some(rule) => out.push_str(&format!(
+            "\nStatus: {} (rule '{}')\n",
+            status.label(),
+            rule.name
+        )),
+        None => out.push_str(&format!(
+            "\nStatus: {} (no rule matched, built-in heuristics)\n",
+            status.label()
+        )),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::ClaudeDetector;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_args_starts_tui() {
+        assert_eq!(parse(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_list_format() {
+        assert_eq!(
+            parse(&args(&["list", "--format", "json"])).unwrap(),
+            Some(Command::List {
+                format: OutputFormat::Json
+            })
+        );
+        assert_eq!(
+            parse(&args(&["list", "--format=tsv"])).unwrap(),
+            Some(Command::List {
+                format: OutputFormat::Tsv
+            })
+        );
+        assert!(parse(&args(&["list", "--format", "xml"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_rename() {
+        assert_eq!(
+            parse(&args(&["rename", "a", "b"])).unwrap(),
+            Some(Command::Rename {
+                old_name: "a".to_string(),
+                new_name: "b".to_string(),
+            })
+        );
+        assert!(parse(&args(&["rename", "a"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_new() {
+        assert_eq!(
+            parse(&args(&["new", "work", "-c", "/tmp", "--shell"])).unwrap(),
+            Some(Command::New {
+                name: Some("work".to_string()),
+                path: Some(PathBuf::from("/tmp")),
+                start_claude: false,
+            })
+        );
+        assert!(parse(&args(&["new", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_detect() {
+        assert_eq!(
+            parse(&args(&[
+                "detect",
+                "--pane",
+                "%3",
+                "--agent",
+                "codex",
+                "--explain"
+            ]))
+            .unwrap(),
+            Some(Command::Detect {
+                pane: Some("%3".to_string()),
+                agent: Some(Agent::Codex),
+                explain: true,
+            })
+        );
+        assert!(parse(&args(&["detect", "--agent", "vim"])).is_err());
+    }
+
+    #[test]
+    fn test_explain_names_matching_rule() {
+        let detector = ClaudeDetector::new(&[]).unwrap();
+        let explanation = explain_detection(&detector, "* (esc to interrupt)\n─────\n❯ ");
+        assert!(explanation.contains("Status: working (rule 'working')"));
+        assert!(explanation.contains("✓"));
+    }
+
+    #[test]
+    fn test_parse_hook() {
+        assert_eq!(
+            parse(&args(&["hook", "stop"])).unwrap(),
+            Some(Command::Hook {
+                event: HookEvent::Stop
+            })
+        );
+        assert_eq!(
+            parse(&args(&["hook", "install", "--settings", "/tmp/s.json"])).unwrap(),
+            Some(Command::HookInstall {
+                settings: Some(PathBuf::from("/tmp/s.json"))
+            })
+        );
+        assert!(parse(&args(&["hook"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_status_line() {
+        assert_eq!(
+            parse(&args(&["status-line", "--template", "{waiting}"])).unwrap(),
+            Some(Command::StatusLine {
+                template: Some("{waiting}".to_string())
+            })
+        );
+        assert!(parse(&args(&["status-line", "extra"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(
+            parse(&args(&["watch"])).unwrap(),
+            Some(Command::Watch {
+                interval: watch::DEFAULT_INTERVAL
+            })
+        );
+        assert_eq!(
+            parse(&args(&["watch", "--interval", "0.5"])).unwrap(),
+            Some(Command::Watch {
+                interval: Duration::from_millis(500)
+            })
+        );
+        assert!(parse(&args(&["watch", "-n", "0"])).is_err());
+        assert!(parse(&args(&["watch", "-n", "-1"])).is_err());
+        assert!(parse(&args(&["watch", "-n", "inf"])).is_err());
+        assert!(parse(&args(&["watch", "-n", "1e300"])).is_err());
+        assert!(parse(&args(&["watch", "-n", "NaN"])).is_err());
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        assert!(parse(&args(&["frobnicate"])).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! User configuration from `~/.config/tsm/config.toml`
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::agents::Agent;
+use crate::notify::Backend;
+use crate::session::ClaudeCodeStatus;
+
+/// Top-level configuration. Every section is optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub detection: DetectionConfig,
+    pub notifications: NotificationConfig,
+    pub mcp: McpConfig,
+    pub status_line: StatusLineConfig,
+}
+
+/// `[detection]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectionConfig {
+    /// Status rules, merged with the built-in rules
+    pub rules: Vec<RuleConfig>,
+}
+
+/// One `[[detection.rules]]` entry
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// Rule name. A rule with the same name as a built-in rule replaces it.
+    pub name: String,
+    /// Agent the rule applies to
+    #[serde(default = "default_rule_agent")]
+    pub agent: Agent,
+    /// Status reported when the rule matches
+    pub status: ClaudeCodeStatus,
+    /// Rules with a higher priority are checked first
+    #[serde(default)]
+    pub priority: i32,
+    /// Substring that must be present
+    pub contains: Option<String>,
+    /// Regular expression that must match
+    pub regex: Option<String>,
+    /// Only look at this line of the capture: 0 is the first line,
+    /// -1 the last
+    pub line: Option<i32>,
+    /// Set to false to turn off a built-in rule of the same name
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// `[notifications]` section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationConfig {
+    pub enabled: bool,
+    /// Delivery methods, all used for every notification
+    pub backends: Vec<Backend>,
+    /// Minimum seconds between two notifications for the same session
+    pub debounce_secs: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            backends: vec![Backend::NotifySend, Backend::Tmux],
+            debounce_secs: 10,
+        }
+    }
+}
+
+/// `[mcp]` section
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct McpConfig {
+    /// Destructive tools (see [`crate::mcp::DESTRUCTIVE_TOOLS`]) offered to
+    /// MCP clients. None by default; e.g. `allow = ["send_keys"]` opts in.
+    pub allow: Vec<String>,
+}
+
+/// `[status_line]` section
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusLineConfig {
+    /// Template for `tsm status-line`, see [`crate::status_line::render`]
+    pub template: String,
+    /// Leave out parts of the template whose counts are all zero
+    pub hide_zero: bool,
+}
+
+impl Default for StatusLineConfig {
+    fn default() -> Self {
+        Self {
+            template: crate::status_line::DEFAULT_TEMPLATE.to_string(),
+            hide_zero: true,
+        }
+    }
+}
+
+fn default_rule_agent() -> Agent {
+    Agent::Claude
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Config {
+    /// Path of the config file: `$XDG_CONFIG_HOME/tsm/config.toml`,
+    /// falling back to `~/.config/tsm/config.toml`
+    pub fn path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(|dir| dir.join("tsm").join("config.toml"))
+    }
+
+    /// Load the config file. A missing file gives the default config.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    /// Parse config file contents
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+/// Directory for state tsm writes at runtime: `$XDG_STATE_HOME/tsm`,
+/// falling back to `~/.local/state/tsm`
+pub fn state_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("state")))
+        .map(|dir| dir.join("tsm"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config() {
+        let config = Config::parse("").unwrap();
+        assert!(config.detection.rules.is_empty());
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let config = Config::parse(
+            r#"
+            [[detection.rules]]
+            name = "trust-prompt"
+            status = "input"
+            priority = 200
+            contains = "Do you trust the files"
+
+            [[detection.rules]]
+            name = "codex-busy"
+            agent = "codex"
+            status = "working"
+            regex = "^\\s*Working"
+            line = -1
+            "#,
+        )
+        .unwrap();
+
+        let rules = &config.detection.rules;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].agent, Agent::Claude);
+        assert_eq!(rules[0].status, ClaudeCodeStatus::WaitingInput);
+        assert_eq!(rules[0].priority, 200);
+        assert!(rules[0].enabled);
+        assert_eq!(rules[1].agent, Agent::Codex);
+        assert_eq!(rules[1].line, Some(-1));
+    }
+
+    #[test]
+    fn test_parse_notifications() {
+        let config = Config::parse(
+            r#"
+            [notifications]
+            backends = ["osc777", "bell"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.notifications.enabled);
+        assert_eq!(
+            config.notifications.backends,
+            vec![Backend::Osc777, Backend::Bell]
+        );
+        assert_eq!(config.notifications.debounce_secs, 10);
+        assert!(Config::parse("[notifications]\nbackends = [\"pager\"]").is_err());
+    }
+
+    #[test]
+    fn test_parse_mcp() {
+        assert!(Config::parse("").unwrap().mcp.allow.is_empty());
+        let config = Config::parse("[mcp]\nallow = [\"kill_session\"]").unwrap();
+        assert_eq!(config.mcp.allow, ["kill_session"]);
+    }
+
+    #[test]
+    fn test_parse_status_line() {
+        let config = Config::parse("[status_line]\ntemplate = \"{waiting}\"").unwrap();
+        assert_eq!(config.status_line.template, "{waiting}");
+        assert!(config.status_line.hide_zero);
+    }
+
+    #[test]
+    fn test_unknown_field_is_an_error() {
+        assert!(Config::parse("[detection]\nrulez = []").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! tmux control-mode client
+//!
+//! Runs `tmux -C attach-session` as a read-only client and turns its
+//! notifications into [`ControlEvent`]s. tmux only sends `%output` for
+//! panes in the session the client is attached to; session and window
+//! notifications arrive for every session.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::thread;
+
+use anyhow::{Context, Result};
+
+/// A notification from the control-mode client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlEvent {
+    /// Sessions, windows or panes were added, removed or rearranged
+    Layout,
+    /// A session was renamed
+    SessionRenamed { session_id: String, name: String },
+    /// A pane in the attached session produced output
+    Output { pane_id: String },
+    /// The control client exited
+    Exit,
+}
+
+/// A running control-mode client. The tmux process is killed on drop.
+pub struct ControlClient {
+    child: Child,
+    /// Commands for the client; it exits when this closes
+    stdin: ChildStdin,
+    /// ID of the session the client is attached to (e.g. "$1")
+    session_id: String,
+}
+
+impl ControlClient {
+    /// Attach a read-only control client to `session_id` and forward its
+    /// notifications to `on_event` from a reader thread
+    pub fn spawn<F>(session_id: &str, on_event: F) -> Result<Self>
+    where
+        F: Fn(ControlEvent) + Send + 'static,
+    {
+        let mut child = Command::new("tmux")
+            .args([
+                "-C",
+                "attach-session",
+                "-t",
+                session_id,
+                "-f",
+                "read-only,ignore-size",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to start tmux control client")?;
+
+        let stdin = child.stdin.take().context("No stdin for control client")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("No stdout for control client")?;
+
+        thread::spawn(move || {
+            let reader = BufReader::new(stdout);
+            // %output lines carry raw pane bytes that may not be UTF-8
+            for line in reader.split(b'\n').map_while(|line| line.ok()) {
+                let line = String::from_utf8_lossy(&line);
+                if let Some(event) = parse_line(&line) {
+                    let exit = event == ControlEvent::Exit;
+                    on_event(event);
+                    if exit {
+                        return;
+                    }
+                }
+            }
+            on_event(ControlEvent::Exit);
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            session_id: session_id.to_string(),
+        })
+    }
+
+    /// ID of the session this client is attached to
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Attach to another session to receive its panes' `%output`
+    pub fn switch_session(&mut self, session_id: &str) -> Result<()> {
+        writeln!(self.stdin, "switch-client -t {}", session_id)
+            .context("Failed to write to control client")?;
+        self.session_id = session_id.to_string();
+        Ok(())
+    }
+}
+
+impl Drop for ControlClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Parse one line of control-mode output into an event.
+/// Command replies and notifications we don't act on return `None`.
+pub fn parse_line(line: &str) -> Option<ControlEvent> {
+    let mut parts = line.splitn(3, ' ');
+    let name = parts.next()?;
+
+    match name {
+        "%output" => Some(ControlEvent::Output {
+            pane_id: parts.next()?.to_string(),
+        }),
+        "%extended-output" => Some(ControlEvent::Output {
+            pane_id: parts.next()?.to_string(),
+        }),
+        "%session-renamed" => Some(ControlEvent::SessionRenamed {
+            session_id: parts.next()?.to_string(),
+            name: parts.next()?.to_string(),
+        }),
+        "%sessions-changed"
+        | "%session-window-changed"
+        | "%client-session-changed"
+        | "%client-detached"
+        | "%window-add"
+        | "%window-close"
+        | "%window-renamed"
+        | "%window-pane-changed"
+        | "%unlinked-window-add"
+        | "%unlinked-window-close"
+        | "%unlinked-window-renamed"
+        | "%layout-change"
+        | "%pane-mode-changed" => Some(ControlEvent::Layout),
+        "%exit" => Some(ControlEvent::Exit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(
+            parse_line("%output %3 hello world\\015\\012"),
+            Some(ControlEvent::Output {
+                pane_id: "%3".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_session_renamed() {
+        assert_eq!(
+            parse_line("%session-renamed $2 my session"),
+            Some(ControlEvent::SessionRenamed {
+                session_id: "$2".to_string(),
+                name: "my session".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_layout_events() {
+        assert_eq!(parse_line("%sessions-changed"), Some(ControlEvent::Layout));
+        assert_eq!(
+            parse_line("%unlinked-window-add @7"),
+            Some(ControlEvent::Layout)
+        );
+        assert_eq!(parse_line("%exit"), Some(ControlEvent::Exit));
+    }
+
+    #[test]
+    fn test_parse_ignored() {
+        assert_eq!(parse_line("%begin 1792279052 509 0"), None);
+        assert_eq!(parse_line("%end 1792279052 509 0"), None);
+        assert_eq!(parse_line(""), None);
+    }
+}
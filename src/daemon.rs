@@ -0,0 +1,325 @@
+//! Background daemon that tracks agent status for all sessions
+//!
+//! `tsm daemon` runs the regular refresh worker without a UI. It keeps a
+//! snapshot of all sessions in the state directory, appends every pane
+//! status change to a history file, sends notifications and sends queued
+//! prompts (see [`crate::queue`]). The TUI and CLI read the snapshot while
+//! it is fresh instead of discovering sessions themselves. The daemon also
+//! serves the JSON API on a Unix socket (see [`crate::api`]).
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::api;
+use crate::config::{self, Config};
+use crate::discovery::Discovery;
+use crate::notify::{self, Notifier};
+use crate::queue;
+use crate::session::{unix_now, ClaudeCodeStatus, Session, StatusClock};
+use crate::worker::{Update, Worker};
+
+/// How often the daemon's worker polls when control mode is unavailable
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The snapshot is rewritten at least this often, even without changes
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A snapshot older than this means the daemon is not running
+const STALE_AFTER: Duration = Duration::from_secs(15);
+
+/// The history file is rotated once it grows past this size
+const HISTORY_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Sessions as last seen by the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonSnapshot {
+    /// Process ID of the daemon
+    pub pid: u32,
+    /// Unix timestamp of the last write
+    pub updated_at: u64,
+    pub sessions: Vec<Session>,
+    /// Unix timestamp of each agent pane's last status change, keyed by pane ID
+    pub since: HashMap<String, u64>,
+}
+
+/// One line of the history file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+    /// Unix timestamp of the change
+    pub time: u64,
+    pub session_id: String,
+    pub session_name: String,
+    pub pane_id: String,
+    pub old: ClaudeCodeStatus,
+    pub new: ClaudeCodeStatus,
+}
+
+/// Path of the snapshot file
+pub fn snapshot_path() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join("snapshot.json"))
+}
+
+/// Path of the status history file (one JSON object per line)
+pub fn history_path() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join("history.ndjson"))
+}
+
+/// Read the daemon's snapshot if a daemon is running, i.e. the snapshot
+/// was written recently
+pub fn read_snapshot() -> Option<DaemonSnapshot> {
+    let text = std::fs::read_to_string(snapshot_path()?).ok()?;
+    let snapshot: DaemonSnapshot = serde_json::from_str(&text).ok()?;
+    (unix_now().saturating_sub(snapshot.updated_at) < STALE_AFTER.as_secs()).then_some(snapshot)
+}
+
+/// Run the daemon in the foreground until it is killed
+pub fn run() -> Result<()> {
+    if let Some(snapshot) = read_snapshot() {
+        if snapshot.pid != std::process::id() {
+            bail!("tsm daemon is already running (pid {})", snapshot.pid);
+        }
+    }
+    let Some(path) = snapshot_path() else {
+        bail!("Could not determine the state directory");
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    let Some(socket) = api::socket_path() else {
+        bail!("Could not determine the state directory");
+    };
+    let server = api::Server::start(&socket)?;
+
+    let config = Config::load()?;
+    let worker = Worker::spawn(Discovery::new(&config)?, POLL_INTERVAL, false);
+    let mut notifier = Notifier::new(config.notifications);
+    let mut daemon = Daemon {
+        sessions: Vec::new(),
+        since: HashMap::new(),
+        clock: StatusClock::default(),
+        written_at: None,
+    };
+
+    loop {
+        let updates = worker.updates();
+        // The worker also reports preview captures, which the daemon ignores
+        let changed = updates.iter().any(|u| !matches!(u, Update::Preview { .. }));
+        let before = notify::pane_statuses(&daemon.sessions);
+        let sessions_before = queue::session_statuses(&daemon.sessions);
+
+        for update in updates {
+            daemon.apply(update);
+        }
+
+        if changed {
+            daemon.clock.stamp(&mut daemon.sessions, unix_now());
+            let changes = daemon.record_changes(&before);
+            server.publish(&daemon.sessions, &changes);
+            notifier.notify(notify::changes(&before, &daemon.sessions));
+            match queue::dispatch(&sessions_before, &daemon.sessions) {
+                Ok(dispatched) => {
+                    for dispatch in dispatched {
+                        if let Some(e) = dispatch.error {
+                            eprintln!("tsm daemon: {}: {}", dispatch.session_name, e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("tsm daemon: {:#}", e),
+            }
+        }
+        if changed
+            || daemon
+                .written_at
+                .is_none_or(|t| t.elapsed() >= HEARTBEAT_INTERVAL)
+        {
+            daemon.write_snapshot()?;
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// State kept by the daemon loop
+struct Daemon {
+    sessions: Vec<Session>,
+    /// Last status change of each agent pane
+    since: HashMap<String, u64>,
+    /// Last status change of each session
+    clock: StatusClock,
+    written_at: Option<Instant>,
+}
+
+impl Daemon {
+    /// Apply one worker update to the session list
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Sessions(snapshot) => match snapshot.sessions {
+                Ok(sessions) => self.sessions = sessions,
+                Err(e) => eprintln!("tsm daemon: {}", e),
+            },
+            Update::SessionRenamed { session_id, name } => {
+                if let Some(session) = self.sessions.iter_mut().find(|s| s.id == session_id) {
+                    session.name = name;
+                }
+            }
+            Update::PaneStatus {
+                pane_id,
+                status,
+                question,
+            } => {
+                for session in &mut self.sessions {
+                    if session.set_pane_status(&pane_id, status, question.clone()) {
+                        break;
+                    }
+                }
+            }
+            Update::Preview { .. } => {}
+        }
+    }
+
+    /// Update change times and append status changes to the history file
+    fn record_changes(&mut self, before: &HashMap<String, ClaudeCodeStatus>) -> Vec<StatusChange> {
+        let now = unix_now();
+        let changes = status_changes(before, &self.sessions, now);
+
+        let live = notify::pane_statuses(&self.sessions);
+        self.since.retain(|pane_id, _| live.contains_key(pane_id));
+        for pane_id in live.keys() {
+            self.since.entry(pane_id.clone()).or_insert(now);
+        }
+        for change in &changes {
+            self.since.insert(change.pane_id.clone(), change.time);
+        }
+
+        if let Err(e) = append_history(&changes) {
+            eprintln!("tsm daemon: {:#}", e);
+        }
+        changes
+    }
+
+    /// Atomically replace the snapshot file
+    fn write_snapshot(&mut self) -> Result<()> {
+        let Some(path) = snapshot_path() else {
+            return Ok(());
+        };
+        let snapshot = DaemonSnapshot {
+            pid: std::process::id(),
+            updated_at: unix_now(),
+            sessions: self.sessions.clone(),
+            since: self.since.clone(),
+        };
+
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(&snapshot)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        self.written_at = Some(Instant::now());
+        Ok(())
+    }
+}
+
+/// Status changes of agent panes from `before` to `sessions`
+fn status_changes(
+    before: &HashMap<String, ClaudeCodeStatus>,
+    sessions: &[Session],
+    now: u64,
+) -> Vec<StatusChange> {
+    let mut changes = Vec::new();
+    for session in sessions {
+        for pane in session.agent_panes() {
+            match before.get(&pane.id) {
+                Some(&old) if old != pane.status => changes.push(StatusChange {
+                    time: now,
+                    session_id: session.id.clone(),
+                    session_name: session.name.clone(),
+                    pane_id: pane.id.clone(),
+                    old,
+                    new: pane.status,
+                }),
+                _ => {}
+            }
+        }
+    }
+    changes
+}
+
+/// Append status changes to the history file, rotating it when too large
+fn append_history(changes: &[StatusChange]) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let Some(path) = history_path() else {
+        return Ok(());
+    };
+
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > HISTORY_MAX_BYTES) {
+        std::fs::rename(&path, path.with_extension("ndjson.1"))
+            .with_context(|| format!("Failed to rotate {}", path.display()))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    for change in changes {
+        writeln!(file, "{}", serde_json::to_string(change)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::Agent;
+    use crate::session::fixtures::session;
+
+    #[test]
+    fn test_status_changes() {
+        let before = notify::pane_statuses(&[session("$1", ClaudeCodeStatus::Working)]);
+
+        let changes = status_changes(&before, &[session("$1", ClaudeCodeStatus::Idle)], 42);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].pane_id, "%1");
+        assert_eq!(changes[0].old, ClaudeCodeStatus::Working);
+        assert_eq!(changes[0].new, ClaudeCodeStatus::Idle);
+        assert_eq!(changes[0].time, 42);
+
+        assert!(
+            status_changes(&before, &[session("$1", ClaudeCodeStatus::Working)], 42).is_empty()
+        );
+        assert!(status_changes(
+            &HashMap::new(),
+            &[session("$1", ClaudeCodeStatus::Idle)],
+            42
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = DaemonSnapshot {
+            pid: 7,
+            updated_at: 100,
+            sessions: vec![session("$1", ClaudeCodeStatus::WaitingInput)],
+            since: HashMap::from([("%1".to_string(), 90)]),
+        };
+        let text = serde_json::to_string(&snapshot).unwrap();
+        let parsed: DaemonSnapshot = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            parsed.sessions[0].claude_code_status,
+            ClaudeCodeStatus::WaitingInput
+        );
+        assert_eq!(parsed.sessions[0].panes[0].agent, Some(Agent::Claude));
+        assert_eq!(parsed.since["%1"], 90);
+    }
+}
@@ -1,7 +1,14 @@
-use crate::session::ClaudeCodeStatus;
+use std::sync::LazyLock;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+use crate::agents::Agent;
+use crate::config::RuleConfig;
+use crate::session::{ClaudeCodeStatus, Question};
 
 /// Strip ANSI escape sequences from text
-fn strip_ansi(s: &str) -> String {
+pub fn strip_ansi(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     let mut chars = s.chars().peekable();
     while let Some(c) = chars.next() {
@@ -23,46 +30,387 @@ fn strip_ansi(s: &str) -> String {
     result
 }
 
-pub fn detect_status(content: &str) -> ClaudeCodeStatus {
-    let content = &strip_ansi(content);
+/// A status rule: when every condition matches, the pane has `status`
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub status: ClaudeCodeStatus,
+    /// Rules with a higher priority are checked first
+    pub priority: i32,
+    /// Substring that must be present
+    pub contains: Option<String>,
+    /// Regular expression that must match
+    pub regex: Option<Regex>,
+    /// Only look at this line: 0 is the first line, -1 the last
+    pub line: Option<i32>,
+}
 
-    if content.contains("Enter to select")
-        || content.contains("↑/↓ to navigate")
-        || content.contains("Esc to cancel")
-        || content.contains("to edit")
-    {
-        return ClaudeCodeStatus::WaitingInput;
+impl Rule {
+    /// Build a rule from its config entry
+    pub fn from_config(config: &RuleConfig) -> Result<Self> {
+        if config.contains.is_none() && config.regex.is_none() {
+            bail!("rule '{}' needs `contains` or `regex`", config.name);
+        }
+
+        let regex = config
+            .regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .with_context(|| format!("rule '{}' has an invalid regex", config.name))?;
+
+        Ok(Self {
+            name: config.name.clone(),
+            status: config.status,
+            priority: config.priority,
+            contains: config.contains.clone(),
+            regex,
+            line: config.line,
+        })
+    }
+
+    /// Check the rule against ANSI-stripped pane content
+    pub fn matches(&self, content: &str) -> bool {
+        let text = match self.line {
+            Some(index) => match select_line(content, index) {
+                Some(line) => line,
+                None => return false,
+            },
+            None => content,
+        };
+
+        let contains = match self.contains {
+            Some(ref needle) => text.contains(needle.as_str()),
+            None => true,
+        };
+        let regex = match self.regex {
+            Some(ref re) => re.is_match(text),
+            None => true,
+        };
+        contains && regex
+    }
+
+    /// Short description of the rule's conditions, for `tsm detect --explain`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(ref contains) = self.contains {
+            parts.push(format!("contains {:?}", contains));
+        }
+        if let Some(ref regex) = self.regex {
+            parts.push(format!("regex /{}/", regex.as_str()));
+        }
+        if let Some(line) = self.line {
+            parts.push(format!("on line {}", line));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Pick a line by index, counting from the end when negative
+fn select_line(content: &str, index: i32) -> Option<&str> {
+    let lines: Vec<&str> = content.lines().collect();
+    let index = if index < 0 {
+        lines.len().checked_sub(index.unsigned_abs() as usize)?
+    } else {
+        index as usize
+    };
+    lines.get(index).copied()
+}
+
+/// Built-in Claude Code rules
+pub fn default_claude_rules() -> Vec<Rule> {
+    // Input field: a prompt line (❯) with a border directly above it
+    const INPUT_FIELD: &str = r"─[^\n]*\n[^\n]*❯";
+
+    let waiting = |name: &str, text: &str| Rule {
+        name: name.to_string(),
+        status: ClaudeCodeStatus::WaitingInput,
+        priority: 100,
+        contains: Some(text.to_string()),
+        regex: None,
+        line: None,
+    };
+
+    vec![
+        waiting("enter-to-select", "Enter to select"),
+        waiting("navigate", "↑/↓ to navigate"),
+        waiting("esc-to-cancel", "Esc to cancel"),
+        waiting("edit", "to edit"),
+        Rule {
+            name: "working".to_string(),
+            status: ClaudeCodeStatus::Working,
+            priority: 50,
+            contains: Some("to interrupt".to_string()),
+            regex: Some(Regex::new(INPUT_FIELD).expect("valid built-in regex")),
+            line: None,
+        },
+        Rule {
+            name: "idle".to_string(),
+            status: ClaudeCodeStatus::Idle,
+            priority: 10,
+            contains: None,
+            regex: Some(Regex::new(INPUT_FIELD).expect("valid built-in regex")),
+            line: None,
+        },
+    ]
+}
+
+/// Merge configured rules for `agent` into `defaults`.
+/// A configured rule replaces the default of the same name, or removes it
+/// when disabled. The result is sorted by priority, highest first;
+/// configured rules win ties.
+pub fn merge_rules(defaults: Vec<Rule>, configs: &[RuleConfig], agent: Agent) -> Result<Vec<Rule>> {
+    let configs: Vec<&RuleConfig> = configs.iter().filter(|c| c.agent == agent).collect();
+
+    let mut rules: Vec<Rule> = Vec::new();
+    for config in configs.iter().filter(|c| c.enabled) {
+        rules.push(Rule::from_config(config)?);
+    }
+    rules.extend(
+        defaults
+            .into_iter()
+            .filter(|d| !configs.iter().any(|c| c.name == d.name)),
+    );
+
+    // Stable sort keeps configured rules ahead of defaults with equal priority
+    rules.sort_by_key(|r| std::cmp::Reverse(r.priority));
+    Ok(rules)
+}
+
+/// The first rule that matches ANSI-stripped content, in the given order
+pub fn first_match<'a>(rules: &'a [Rule], content: &str) -> Option<&'a Rule> {
+    rules.iter().find(|rule| rule.matches(content))
+}
+
+/// A numbered menu an agent is waiting on, e.g. a permission prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu {
+    /// Text right above the options, like "Do you want to proceed?"
+    pub question: Option<String>,
+    pub options: Vec<MenuOption>,
+}
+
+impl Menu {
+    /// Whether two captures show the same prompt: the same question and
+    /// options, wherever the cursor is
+    pub fn same_prompt(&self, other: &Menu) -> bool {
+        self.question == other.question
+            && self.options.len() == other.options.len()
+            && self
+                .options
+                .iter()
+                .zip(&other.options)
+                .all(|(a, b)| a.number == b.number && a.label == b.label)
     }
-    if has_input_field(content) {
-        if content.contains("to interrupt") {
-            return ClaudeCodeStatus::Working;
+
+    /// Look up the option for digits typed so far
+    pub fn typed_option(&self, typed: &str) -> TypedOption {
+        let mut matches = self
+            .options
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.number.to_string().starts_with(typed));
+        let Some((first, _)) = matches.next() else {
+            return TypedOption::NoMatch;
+        };
+        if matches.next().is_none() {
+            return TypedOption::Picked(first);
         }
-        return ClaudeCodeStatus::Idle;
+        let exact = self
+            .options
+            .iter()
+            .position(|o| o.number.to_string() == typed);
+        TypedOption::Partial(exact.unwrap_or(first))
     }
 
-    ClaudeCodeStatus::Unknown
+    /// Keys that answer with the option at `index`: its number, or for
+    /// two-digit numbers, which the agent would read as two picks, arrow
+    /// keys from the highlighted option and Enter
+    pub fn reply_keys(&self, index: usize) -> Option<Vec<String>> {
+        let option = self.options.get(index)?;
+        if option.number < 10 {
+            return Some(vec![option.number.to_string()]);
+        }
+        let cursor = self.options.iter().position(|o| o.highlighted)?;
+        let key = if index > cursor { "Down" } else { "Up" };
+        let mut keys = vec![key.to_string(); index.abs_diff(cursor)];
+        keys.push("Enter".to_string());
+        Some(keys)
+    }
 }
 
-/// Detect input field: prompt line (❯) with border directly above it.
-fn has_input_field(content: &str) -> bool {
+/// Where digits typed in the quick-reply dialog lead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypedOption {
+    /// Only this option's number starts with the digits
+    Picked(usize),
+    /// Several numbers start with the digits; this option is the closest
+    Partial(usize),
+    /// No option's number starts with the digits
+    NoMatch,
+}
+
+/// One option of a [`Menu`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuOption {
+    /// Number typed to pick the option
+    pub number: u32,
+    pub label: String,
+    /// Whether the agent's cursor (❯) is on this option
+    pub highlighted: bool,
+}
+
+/// Find the last numbered menu ("1. Yes", "❯ 2. No", ...) in pane content.
+/// Lines between options, such as wrapped labels, are skipped.
+pub fn parse_menu(content: &str) -> Option<Menu> {
+    let content = strip_ansi(content);
     let lines: Vec<&str> = content.lines().collect();
+    find_menu(&lines).map(|(_, menu)| menu)
+}
 
+/// A menu option line: optional cursor, number, label
+static OPTION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([❯›>]\s*)?(\d{1,2})[.)]\s+(.+?)\s*$").expect("valid built-in regex")
+});
+
+/// The last menu in ANSI-stripped lines, with the index of its first option
+fn find_menu(lines: &[&str]) -> Option<(usize, Menu)> {
+    let mut menu: Option<(usize, Menu)> = None;
+    let mut current: Option<(usize, Menu)> = None;
     for (i, line) in lines.iter().enumerate() {
-        if line.contains('❯') {
-            // Check if line above is a border
-            if i > 0 && lines[i - 1].contains('─') {
-                return true;
+        let Some(caps) = OPTION_RE.captures(clean_box_line(line)) else {
+            continue;
+        };
+        let number: u32 = caps[2].parse().unwrap_or(0);
+        let option = MenuOption {
+            number,
+            label: caps[3].to_string(),
+            highlighted: caps.get(1).is_some(),
+        };
+
+        let continues = current
+            .as_ref()
+            .and_then(|(_, m)| m.options.last())
+            .is_some_and(|last| last.number + 1 == number);
+        if continues {
+            if let Some((_, ref mut m)) = current {
+                m.options.push(option);
+            }
+        } else if number == 1 {
+            if let Some(done) = current.take().filter(|(_, m)| m.options.len() > 1) {
+                menu = Some(done);
             }
+            let question = lines[..i]
+                .iter()
+                .map(|l| clean_box_line(l))
+                .rfind(|l| !l.is_empty())
+                .map(str::to_string);
+            current = Some((
+                i,
+                Menu {
+                    question,
+                    options: vec![option],
+                },
+            ));
         }
     }
 
-    false
+    current.filter(|(_, m)| m.options.len() > 1).or(menu)
+}
+
+/// Pull what a waiting agent is asking out of pane content: the text
+/// between the top of the prompt's box (or a separator line) and its menu.
+/// Plan approvals use the plan as the subject.
+pub fn parse_question(content: &str) -> Option<Question> {
+    let content = strip_ansi(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let (start, _) = find_menu(&lines)?;
+    let cleaned: Vec<&str> = lines[..start].iter().map(|l| clean_box_line(l)).collect();
+    let question_index = cleaned.iter().rposition(|l| !l.is_empty())?;
+    let question = cleaned[question_index];
+    let above = &cleaned[..question_index];
+
+    let text_lines = |from: usize| -> Vec<&str> {
+        above[from..]
+            .iter()
+            .filter(|l| !l.is_empty() && !is_border(l))
+            .copied()
+            .chain(std::iter::once(question))
+            .collect()
+    };
+
+    if let Some(header) = above
+        .iter()
+        .rposition(|l| l.contains("Here is Claude's plan"))
+    {
+        let text = text_lines(header + 1);
+        return Some(Question {
+            kind: Some("Plan".to_string()),
+            subject: (text.len() > 1).then(|| text[0].to_string()),
+            text: text.join("\n"),
+        });
+    }
+
+    let Some(top) = prompt_top(above) else {
+        return Some(Question {
+            kind: None,
+            subject: None,
+            text: question.to_string(),
+        });
+    };
+    let text = text_lines(top + 1);
+    let block = &text[..text.len() - 1];
+    Some(Question {
+        kind: block.first().map(|l| l.to_string()),
+        subject: block.get(1).map(|l| l.to_string()),
+        text: text.join("\n"),
+    })
+}
+
+/// Index of the line that opens the prompt: the nearest box top or
+/// separator above it, skipping boxes nested inside the prompt
+fn prompt_top(lines: &[&str]) -> Option<usize> {
+    let mut depth = 0;
+    for (i, line) in lines.iter().enumerate().rev() {
+        if line.starts_with('╰') {
+            depth += 1;
+        } else if line.starts_with('╭') {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+        } else if depth == 0 && is_separator(line) {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Trim whitespace and the vertical borders of (nested) boxes
+fn clean_box_line(line: &str) -> &str {
+    line.trim_matches(|c: char| c == '│' || c.is_whitespace())
+}
+
+/// A horizontal rule like "──────"
+fn is_separator(line: &str) -> bool {
+    line.chars().count() >= 3 && line.chars().all(|c| c == '─' || c == '━')
+}
+
+/// A box edge or separator line
+fn is_border(line: &str) -> bool {
+    line.starts_with('╭') || line.starts_with('╰') || is_separator(line)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn detect_status(content: &str) -> ClaudeCodeStatus {
+        first_match(&default_claude_rules(), &strip_ansi(content))
+            .map(|rule| rule.status)
+            .unwrap_or(ClaudeCodeStatus::Unknown)
+    }
+
     #[test]
     fn test_working() {
         let content = "* (ctrl+c to interrupt)\n─────\n❯ hello";
@@ -99,6 +447,145 @@ mod tests {
         assert_eq!(detect_status(content), ClaudeCodeStatus::WaitingInput);
     }
 
+    #[test]
+    fn test_parse_menu() {
+        let content = "Do you want to proceed?\n  1. Yes\n❯ 2. No\n\nEsc to cancel";
+        let menu = parse_menu(content).unwrap();
+        assert_eq!(menu.question.as_deref(), Some("Do you want to proceed?"));
+        assert_eq!(menu.options.len(), 2);
+        assert_eq!(menu.options[0].label, "Yes");
+        assert!(!menu.options[0].highlighted);
+        assert_eq!(menu.options[1].number, 2);
+        assert!(menu.options[1].highlighted);
+    }
+
+    #[test]
+    fn test_parse_menu_takes_last_menu() {
+        let content = "Steps:\n1. Read\n2. Edit\n\nDo you want to make this edit?\n\x1b[36m❯ 1. Yes\x1b[39m\n  2. Yes, allow all edits\n     during this session\n  3. No";
+        let menu = parse_menu(content).unwrap();
+        assert_eq!(
+            menu.question.as_deref(),
+            Some("Do you want to make this edit?")
+        );
+        let labels: Vec<&str> = menu.options.iter().map(|o| o.label.as_str()).collect();
+        assert_eq!(labels, ["Yes", "Yes, allow all edits", "No"]);
+    }
+
+    #[test]
+    fn test_same_prompt() {
+        let menu = parse_menu("Proceed?\n❯ 1. Yes\n  2. No").unwrap();
+        let moved = parse_menu("Proceed?\n  1. Yes\n❯ 2. No").unwrap();
+        let other = parse_menu("Edit file?\n❯ 1. Yes\n  2. No").unwrap();
+        assert!(menu.same_prompt(&moved));
+        assert!(!menu.same_prompt(&other));
+    }
+
+    #[test]
+    fn test_typed_option() {
+        let short = parse_menu("Proceed?\n❯ 1. Yes\n  2. No").unwrap();
+        assert_eq!(short.typed_option("2"), TypedOption::Picked(1));
+        assert_eq!(short.typed_option("0"), TypedOption::NoMatch);
+        assert_eq!(short.typed_option("3"), TypedOption::NoMatch);
+
+        let options: Vec<String> = (1..=12).map(|n| format!("{}. Option {}", n, n)).collect();
+        let long = parse_menu(&format!("Pick one\n❯ {}", options.join("\n"))).unwrap();
+        assert_eq!(long.typed_option("1"), TypedOption::Partial(0));
+        assert_eq!(long.typed_option("12"), TypedOption::Picked(11));
+        assert_eq!(long.typed_option("2"), TypedOption::Picked(1));
+        assert_eq!(long.typed_option("13"), TypedOption::NoMatch);
+    }
+
+    #[test]
+    fn test_reply_keys() {
+        let options: Vec<String> = (1..=11).map(|n| format!("{}. Option {}", n, n)).collect();
+        let mut menu = parse_menu(&format!("Pick one\n{}", options.join("\n"))).unwrap();
+        menu.options[9].highlighted = true;
+        assert_eq!(menu.reply_keys(2), Some(vec!["3".to_string()]));
+        assert_eq!(
+            menu.reply_keys(10),
+            Some(vec!["Down".to_string(), "Enter".to_string()])
+        );
+
+        menu.options[9].highlighted = false;
+        assert_eq!(menu.reply_keys(10), None);
+    }
+
+    #[test]
+    fn test_parse_menu_none() {
+        assert_eq!(parse_menu("● Done\n─────\n❯ hello"), None);
+        assert_eq!(parse_menu("1. only one option"), None);
+    }
+
+    #[test]
+    fn test_parse_question_bash() {
+        let content = "\
+╭───────────────────────────────────────╮
+│ Bash command                          │
+│                                       │
+│   cargo test --workspace              │
+│   Run the test suite                  │
+│                                       │
+│ Do you want to proceed?               │
+│ ❯ 1. Yes                              │
+│   2. No, and tell Claude what to do   │
+╰───────────────────────────────────────╯";
+        let question = parse_question(content).unwrap();
+        assert_eq!(question.kind.as_deref(), Some("Bash command"));
+        assert_eq!(question.subject.as_deref(), Some("cargo test --workspace"));
+        assert_eq!(question.summary(), "Bash command: cargo test --workspace");
+        assert_eq!(
+            question.text,
+            "Bash command\ncargo test --workspace\nRun the test suite\nDo you want to proceed?"
+        );
+    }
+
+    #[test]
+    fn test_parse_question_edit_with_nested_box() {
+        let content = "\
+● Updating the parser
+────────────────────────────────────────
+ Edit file
+ ╭──────────────────────────────╮
+ │ src/parser.rs                │
+ │  12 - let x = 1;             │
+ │  12 + let x = 2;             │
+ ╰──────────────────────────────╯
+ Do you want to make this edit to parser.rs?
+ ❯ 1. Yes
+   2. No";
+        let question = parse_question(content).unwrap();
+        assert_eq!(question.summary(), "Edit file: src/parser.rs");
+    }
+
+    #[test]
+    fn test_parse_question_plan() {
+        let content = "\
+╭──────────────────────────────────────────╮
+│ Ready to code?                           │
+│ Here is Claude's plan:                   │
+│ ╭──────────────────────────────────────╮ │
+│ │ Refactor the parser                  │ │
+│ │ 1. Split the lexer out               │ │
+│ │ 2. Add tests                         │ │
+│ ╰──────────────────────────────────────╯ │
+│ Would you like to proceed?               │
+│ ❯ 1. Yes, and auto-accept edits          │
+│   2. Yes, and manually approve edits     │
+│   3. No, keep planning                   │
+╰──────────────────────────────────────────╯";
+        let question = parse_question(content).unwrap();
+        assert_eq!(question.summary(), "Plan: Refactor the parser");
+        assert!(question.text.contains("2. Add tests"));
+        assert!(question.text.ends_with("Would you like to proceed?"));
+    }
+
+    #[test]
+    fn test_parse_question_without_box() {
+        let question = parse_question("Do you want to proceed?\n  1. Yes\n❯ 2. No").unwrap();
+        assert_eq!(question.summary(), "Do you want to proceed?");
+        assert_eq!(parse_question("● Done\n─────\n❯ hello"), None);
+    }
+
     #[test]
     fn test_waiting_input_with_ansi() {
         let content = "\x1b[38;2;153;153;153mEsc\x1b[39m \x1b[38;2;153;153;153mto\x1b[39m \x1b[38;2;153;153;153mcancel\x1b[39m";
@@ -122,4 +609,72 @@ mod tests {
         let content = "random stuff";
         assert_eq!(detect_status(content), ClaudeCodeStatus::Unknown);
     }
+
+    fn rule_config(name: &str, contains: &str, priority: i32) -> RuleConfig {
+        RuleConfig {
+            name: name.to_string(),
+            agent: Agent::Claude,
+            status: ClaudeCodeStatus::WaitingInput,
+            priority,
+            contains: Some(contains.to_string()),
+            regex: None,
+            line: None,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_line_position() {
+        let mut config = rule_config("last", "done", 0);
+        config.line = Some(-1);
+        let rule = Rule::from_config(&config).unwrap();
+        assert!(rule.matches("working\nall done"));
+        assert!(!rule.matches("all done\nworking"));
+
+        config.line = Some(5);
+        let rule = Rule::from_config(&config).unwrap();
+        assert!(!rule.matches("all done"));
+    }
+
+    #[test]
+    fn test_merge_rules_priority_and_override() {
+        let configs = vec![
+            rule_config("trust", "Do you trust", 200),
+            rule_config("edit", "ctrl+e to edit", 100),
+        ];
+        let rules = merge_rules(default_claude_rules(), &configs, Agent::Claude).unwrap();
+
+        assert_eq!(rules[0].name, "trust");
+        // The configured "edit" rule replaced the built-in one
+        let edit: Vec<&Rule> = rules.iter().filter(|r| r.name == "edit").collect();
+        assert_eq!(edit.len(), 1);
+        assert_eq!(edit[0].contains.as_deref(), Some("ctrl+e to edit"));
+        assert_eq!(
+            first_match(&rules, "press e to edit").map(|r| r.name.as_str()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_merge_rules_disable_default() {
+        let mut config = rule_config("esc-to-cancel", "", 0);
+        config.enabled = false;
+        let rules = merge_rules(default_claude_rules(), &[config], Agent::Claude).unwrap();
+        assert!(first_match(&rules, "Esc to cancel").is_none());
+    }
+
+    #[test]
+    fn test_merge_rules_other_agent_ignored() {
+        let mut config = rule_config("codex-only", "anything", 0);
+        config.agent = Agent::Codex;
+        let rules = merge_rules(default_claude_rules(), &[config], Agent::Claude).unwrap();
+        assert!(rules.iter().all(|r| r.name != "codex-only"));
+    }
+
+    #[test]
+    fn test_rule_needs_a_condition() {
+        let mut config = rule_config("empty", "", 0);
+        config.contains = None;
+        assert!(Rule::from_config(&config).is_err());
+    }
 }
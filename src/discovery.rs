@@ -0,0 +1,561 @@
+//! Session discovery from a single batched tmux query
+//!
+//! One `list-panes -a` call returns every pane together with its session
+//! and window fields. Process names and agent status are cached
+//! between refreshes so the number of spawned processes per refresh stays
+//! roughly constant as the number of sessions grows.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::agents::{detectors, Agent, AgentDetector};
+use crate::config::Config;
+use crate::detection::parse_question;
+use crate::hooks::{self, HookState};
+use crate::session::{unix_now, ClaudeCodeStatus, Pane, Question, Session, Window};
+use crate::tmux::Tmux;
+
+/// Combined session/window/pane format for `list-panes -a`.
+/// `pane_title` comes last so a title containing tabs stays intact.
+const PANE_FORMAT: &str = "#{session_id}\t#{session_name}\t#{session_created}\t#{session_attached}\t#{session_windows}\t#{window_activity}\t#{window_index}\t#{pane_id}\t#{pane_pid}\t#{pane_current_command}\t#{pane_current_path}\t#{@tsm-muted}\t#{window_active}\t#{pane_active}\t#{window_name}\t#{pane_title}";
+
+/// Number of fields in [`PANE_FORMAT`]
+const PANE_FIELDS: usize = 16;
+
+/// Re-capture an agent pane at least this often, even without new activity.
+/// `window_activity` only has one-second resolution.
+const STATUS_TTL: Duration = Duration::from_secs(2);
+
+/// Lines captured to read the question of a waiting agent
+const QUESTION_LINES: usize = 40;
+
+/// One line of `list-panes -a` output
+#[derive(Debug, Clone)]
+struct PaneRecord {
+    session_id: String,
+    session_name: String,
+    session_created: i64,
+    session_attached: u32,
+    session_windows: usize,
+    window_activity: i64,
+    /// Value of the `@tsm-muted` session option
+    session_muted: bool,
+    window: Window,
+    pane: Pane,
+}
+
+/// Status detected for a pane, with the activity stamp it was captured at
+struct CachedStatus {
+    agent: Agent,
+    window_activity: i64,
+    checked_at: Instant,
+    status: ClaudeCodeStatus,
+}
+
+/// Discovers sessions and keeps caches across refreshes
+pub struct Discovery {
+    /// Agent detectors, tried in order
+    detectors: Vec<Box<dyn AgentDetector>>,
+    /// Command line of each pane's root process, keyed by pid
+    processes: HashMap<u32, String>,
+    /// Last detected status, keyed by pane id
+    statuses: HashMap<String, CachedStatus>,
+    /// Question of each waiting pane with the activity stamp it was read at
+    questions: HashMap<String, (i64, Option<Question>)>,
+}
+
+impl Discovery {
+    /// Create a discovery using the detection rules from `config`
+    pub fn new(config: &Config) -> Result<Self> {
+        Ok(Self {
+            detectors: detectors(&config.detection)?,
+            processes: HashMap::new(),
+            statuses: HashMap::new(),
+            questions: HashMap::new(),
+        })
+    }
+
+    /// The detector for `agent`
+    pub fn detector(&self, agent: Agent) -> Option<&dyn AgentDetector> {
+        self.detectors
+            .iter()
+            .find(|d| d.agent() == agent)
+            .map(|d| d.as_ref())
+    }
+
+    /// List all tmux sessions with their metadata
+    pub fn list_sessions(&mut self) -> Result<Vec<Session>> {
+        let output = Command::new("tmux")
+            .args(["list-panes", "-a", "-F", PANE_FORMAT])
+            .output()
+            .context("Failed to execute tmux list-panes")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // No sessions is not an error for us
+            if stderr.contains("no server running") || stderr.contains("no sessions") {
+                self.processes.clear();
+                self.statuses.clear();
+                return Ok(Vec::new());
+            }
+            anyhow::bail!("tmux list-panes failed: {}", stderr);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let records: Vec<PaneRecord> = stdout.lines().filter_map(parse_pane_line).collect();
+
+        self.resolve_processes(&records);
+        self.prune(&records);
+        let clients = attached_clients();
+
+        let mut sessions = Vec::new();
+        for group in group_by_session(records) {
+            sessions.push(self.build_session(group, clients.as_ref()));
+        }
+
+        // Sort by attached status first, then by name
+        sessions.sort_by(|a, b| {
+            b.attached
+                .cmp(&a.attached)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        Ok(sessions)
+    }
+
+    /// Look up the root process of every pane not seen before, with one `ps` call
+    fn resolve_processes(&mut self, records: &[PaneRecord]) {
+        let unknown: Vec<u32> = records
+            .iter()
+            .map(|r| r.pane.pid)
+            .filter(|pid| *pid > 0 && !self.processes.contains_key(pid))
+            .collect();
+
+        if unknown.is_empty() {
+            return;
+        }
+
+        let pid_list = unknown
+            .iter()
+            .map(|pid| pid.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let commands = Command::new("ps")
+            .args(["-o", "pid=,command=", "-p", &pid_list])
+            .output()
+            .map(|output| parse_ps_output(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or_default();
+
+        for pid in unknown {
+            let command = commands.get(&pid).cloned().unwrap_or_default();
+            self.processes.insert(pid, command);
+        }
+    }
+
+    /// Drop cache entries for panes and processes that no longer exist
+    fn prune(&mut self, records: &[PaneRecord]) {
+        let pids: HashSet<u32> = records.iter().map(|r| r.pane.pid).collect();
+        let pane_ids: HashSet<&str> = records.iter().map(|r| r.pane.id.as_str()).collect();
+
+        self.processes.retain(|pid, _| pids.contains(pid));
+        self.statuses.retain(|id, _| pane_ids.contains(id.as_str()));
+        self.questions
+            .retain(|id, _| pane_ids.contains(id.as_str()));
+    }
+
+    /// Assemble a session from its pane records. `clients` counts the
+    /// attached clients of each session, when they could be listed.
+    fn build_session(
+        &mut self,
+        records: Vec<PaneRecord>,
+        clients: Option<&HashMap<String, u32>>,
+    ) -> Session {
+        let first = &records[0];
+        let id = first.session_id.clone();
+        let name = first.session_name.clone();
+        let created = first.session_created;
+        let attached = match clients {
+            Some(clients) => clients.contains_key(&id),
+            None => first.session_attached > 0,
+        };
+        let window_count = first.session_windows;
+        let muted = first.session_muted;
+
+        let mut windows: Vec<Window> = Vec::new();
+        for record in &records {
+            if !windows.iter().any(|w| w.index == record.window.index) {
+                windows.push(record.window.clone());
+            }
+        }
+        windows.sort_by_key(|w| w.index);
+
+        // Detect the agent and status of every pane
+        let mut panes = Vec::with_capacity(records.len());
+        for record in &records {
+            let mut pane = record.pane.clone();
+            pane.agent = self.detect_agent(&pane);
+            if let Some(agent) = pane.agent {
+                pane.status = self.pane_status(record, agent);
+                pane.question = self.question(&pane, record.window_activity);
+            }
+            panes.push(pane);
+        }
+
+        // Fallback: session name starts with "claude" (e.g. claude_284B7F7A)
+        if panes.iter().all(|p| p.agent.is_none()) && name.starts_with("claude") {
+            panes[0].agent = Some(Agent::Claude);
+            panes[0].status = self.pane_status(first, Agent::Claude);
+            panes[0].question = self.question(&panes[0].clone(), first.window_activity);
+        }
+
+        // Path and title come from the first agent pane, or the first pane
+        let main_pane = panes
+            .iter()
+            .find(|p| p.agent.is_some())
+            .unwrap_or(&panes[0]);
+        let working_directory = main_pane.current_path.clone();
+        let pane_title = main_pane.title.clone();
+
+        let mut session = Session {
+            id,
+            name,
+            created,
+            attached,
+            working_directory,
+            window_count,
+            windows,
+            panes,
+            agent: None,
+            claude_code_pane: None,
+            claude_code_status: ClaudeCodeStatus::Unknown,
+            pane_title,
+            muted,
+            status_since: None,
+        };
+        session.update_agent_summary();
+        session
+    }
+
+    /// Find which agent, if any, runs in a pane
+    fn detect_agent(&self, pane: &Pane) -> Option<Agent> {
+        let process = self.processes.get(&pane.pid).map(String::as_str);
+        self.detectors
+            .iter()
+            .find(|d| d.matches(pane, process))
+            .map(|d| d.agent())
+    }
+
+    /// Detect a pane's status. Fresh hook state wins; otherwise the screen
+    /// is scraped, reusing the cached value while the window is quiet.
+    fn pane_status(&mut self, record: &PaneRecord, agent: Agent) -> ClaudeCodeStatus {
+        let hook = hook_state(&record.pane.id, agent);
+        resolve_status(hook.as_ref(), unix_now(), || {
+            self.scraped_status(record, agent)
+        })
+    }
+
+    /// Status from the screen, cached while the window is quiet
+    fn scraped_status(&mut self, record: &PaneRecord, agent: Agent) -> ClaudeCodeStatus {
+        if let Some(cached) = self.statuses.get(&record.pane.id) {
+            if cached.agent == agent
+                && cached.window_activity == record.window_activity
+                && cached.checked_at.elapsed() < STATUS_TTL
+            {
+                return cached.status;
+            }
+        }
+
+        self.capture_status(&record.pane.id, agent, record.window_activity)
+    }
+
+    /// Re-detect an agent pane's status and question right away, e.g.
+    /// after it produced output
+    pub fn refresh_pane_status(&mut self, pane_id: &str) -> (ClaudeCodeStatus, Option<Question>) {
+        let (agent, activity) = self
+            .statuses
+            .get(pane_id)
+            .map(|cached| (cached.agent, cached.window_activity))
+            .unwrap_or((Agent::Claude, 0));
+        let hook = hook_state(pane_id, agent);
+        let status = resolve_status(hook.as_ref(), unix_now(), || {
+            self.capture_status(pane_id, agent, activity)
+        });
+
+        let question = (status == ClaudeCodeStatus::WaitingInput)
+            .then(|| self.read_question(pane_id, activity))
+            .flatten();
+        (status, question)
+    }
+
+    /// What a waiting agent pane is asking, re-read only after the window
+    /// had activity
+    fn question(&mut self, pane: &Pane, window_activity: i64) -> Option<Question> {
+        if pane.status != ClaudeCodeStatus::WaitingInput {
+            self.questions.remove(&pane.id);
+            return None;
+        }
+        match self.questions.get(&pane.id) {
+            Some((activity, question)) if *activity == window_activity => question.clone(),
+            _ => self.read_question(&pane.id, window_activity),
+        }
+    }
+
+    /// Capture a pane and parse its question, falling back to the
+    /// notification Claude Code's hooks recorded
+    fn read_question(&mut self, pane_id: &str, window_activity: i64) -> Option<Question> {
+        let question = Tmux::capture_pane(pane_id, QUESTION_LINES, false)
+            .ok()
+            .and_then(|content| parse_question(&content))
+            .or_else(|| {
+                let state = hooks::read_state(pane_id)?;
+                Some(Question {
+                    kind: state.tool_name,
+                    subject: None,
+                    text: state.message?,
+                })
+            });
+        self.questions
+            .insert(pane_id.to_string(), (window_activity, question.clone()));
+        question
+    }
+
+    /// Capture a pane, detect its status with the agent's detector and cache the result
+    fn capture_status(
+        &mut self,
+        pane_id: &str,
+        agent: Agent,
+        window_activity: i64,
+    ) -> ClaudeCodeStatus {
+        let detector = self.detector(agent);
+        let status = match (detector, capture_status_lines(pane_id)) {
+            (Some(detector), Ok(content)) => detector.detect_status(&content),
+            _ => ClaudeCodeStatus::Unknown,
+        };
+
+        self.statuses.insert(
+            pane_id.to_string(),
+            CachedStatus {
+                agent,
+                window_activity,
+                checked_at: Instant::now(),
+                status,
+            },
+        );
+
+        status
+    }
+}
+
+/// State recorded by Claude Code's hooks, which is more reliable than
+/// scraping the screen when available
+fn hook_state(pane_id: &str, agent: Agent) -> Option<HookState> {
+    if agent != Agent::Claude {
+        return None;
+    }
+    hooks::read_state(pane_id)
+}
+
+/// Combine hook state with the screen. Fresh hook state is trusted as is;
+/// older state only stands unless the screen clearly shows another status,
+/// e.g. after a crash or a missed hook.
+fn resolve_status(
+    hook: Option<&HookState>,
+    now: u64,
+    scrape: impl FnOnce() -> ClaudeCodeStatus,
+) -> ClaudeCodeStatus {
+    match hook {
+        Some(state) if state.is_fresh(now) => state.status,
+        Some(state) => match scrape() {
+            ClaudeCodeStatus::Unknown => state.status,
+            scraped => scraped,
+        },
+        None => scrape(),
+    }
+}
+
+/// Capture the pane lines status detection looks at: the last few
+/// non-empty lines
+pub fn capture_status_lines(pane_id: &str) -> Result<String> {
+    Tmux::capture_pane(pane_id, 4, true)
+}
+
+/// Parse one line of [`PANE_FORMAT`] output
+fn parse_pane_line(line: &str) -> Option<PaneRecord> {
+    let parts: Vec<&str> = line.splitn(PANE_FIELDS, '\t').collect();
+    if parts.len() < PANE_FIELDS {
+        return None;
+    }
+
+    Some(PaneRecord {
+        session_id: parts[0].to_string(),
+        session_name: parts[1].to_string(),
+        session_created: parts[2].parse().unwrap_or(0),
+        session_attached: parts[3].parse().unwrap_or(0),
+        session_windows: parts[4].parse().unwrap_or(1),
+        window_activity: parts[5].parse().unwrap_or(0),
+        session_muted: !matches!(parts[11], "" | "0"),
+        window: Window {
+            index: parts[6].parse().unwrap_or(0),
+            name: parts[14].to_string(),
+            active: parts[12] == "1",
+        },
+        pane: Pane {
+            id: parts[7].to_string(),
+            window_index: parts[6].parse().unwrap_or(0),
+            pid: parts[8].parse().unwrap_or(0),
+            current_command: parts[9].to_string(),
+            current_path: PathBuf::from(parts[10]),
+            title: parts[15].to_string(),
+            agent: None,
+            status: ClaudeCodeStatus::Unknown,
+            question: None,
+            active: parts[13] == "1",
+        },
+    })
+}
+
+/// Group pane records by session, keeping tmux's order
+fn group_by_session(records: Vec<PaneRecord>) -> Vec<Vec<PaneRecord>> {
+    let mut groups: Vec<Vec<PaneRecord>> = Vec::new();
+
+    for record in records {
+        match groups
+            .iter_mut()
+            .find(|g| g[0].session_id == record.session_id)
+        {
+            Some(group) => group.push(record),
+            None => groups.push(vec![record]),
+        }
+    }
+
+    groups
+}
+
+/// Parse `ps -o pid=,command=` output into a pid → command map
+fn parse_ps_output(output: &str) -> HashMap<u32, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim_start().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, command.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Number of clients attached to each session, by session ID. Control-mode
+/// clients, like the ones tsm itself uses to follow changes, don't count.
+fn attached_clients() -> Option<HashMap<String, u32>> {
+    let output = Command::new("tmux")
+        .args([
+            "list-clients",
+            "-F",
+            "#{session_id}\t#{client_control_mode}",
+        ])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_client_counts(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `list-clients` output into attached client counts
+fn parse_client_counts(output: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for line in output.lines() {
+        if let Some((session_id, "0")) = line.split_once('\t') {
+            *counts.entry(session_id.to_string()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pane_line() {
+        let line = "$1\twork\t1700000000\t2\t3\t1700000100\t2\t%4\t123\tzsh\t/home/me/src\t1\t1\t0\tedit\ttitle\twith tab";
+        let record = parse_pane_line(line).unwrap();
+        assert_eq!(record.session_id, "$1");
+        assert_eq!(record.session_name, "work");
+        assert_eq!(record.session_attached, 2);
+        assert_eq!(record.session_windows, 3);
+        assert_eq!(record.window_activity, 1700000100);
+        assert_eq!(record.pane.id, "%4");
+        assert_eq!(record.pane.window_index, 2);
+        assert_eq!(record.pane.pid, 123);
+        assert!(record.session_muted);
+        assert_eq!(record.pane.current_path, PathBuf::from("/home/me/src"));
+        assert_eq!(record.pane.title, "title\twith tab");
+        assert!(!record.pane.active);
+        assert_eq!(
+            record.window,
+            Window {
+                index: 2,
+                name: "edit".to_string(),
+                active: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_status() {
+        use ClaudeCodeStatus::{Idle, Unknown, Working};
+
+        let state = HookState {
+            status: Working,
+            event: "PreToolUse".to_string(),
+            updated_at: 1000,
+            message: None,
+            tool_name: None,
+        };
+        let (idle, unknown) = (|| Idle, || Unknown);
+
+        assert_eq!(resolve_status(Some(&state), 1010, idle), Working);
+        assert_eq!(resolve_status(Some(&state), 2000, idle), Idle);
+        assert_eq!(resolve_status(Some(&state), 2000, unknown), Working);
+        assert_eq!(resolve_status(None, 1010, idle), Idle);
+    }
+
+    #[test]
+    fn test_parse_client_counts() {
+        let counts = parse_client_counts("$0\t0\n$0\t1\n$1\t1\n$2\t0\n$2\t0\n");
+        assert_eq!(counts.get("$0"), Some(&1));
+        assert_eq!(counts.get("$1"), None);
+        assert_eq!(counts.get("$2"), Some(&2));
+    }
+
+    #[test]
+    fn test_parse_pane_line_too_short() {
+        assert!(parse_pane_line("work\t1700000000").is_none());
+    }
+
+    #[test]
+    fn test_group_by_session() {
+        let lines = [
+            "$0\ta\t1\t0\t1\t0\t0\t%0\t1\tzsh\t/\t\t1\t1\tzsh\t",
+            "$1\tb\t2\t0\t1\t0\t0\t%1\t2\tzsh\t/\t\t1\t1\tzsh\t",
+            "$0\ta\t1\t0\t1\t0\t1\t%2\t3\tzsh\t/\t\t0\t1\tzsh\t",
+        ];
+        let records = lines.iter().filter_map(|l| parse_pane_line(l)).collect();
+        let groups = group_by_session(records);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].len(), 2);
+        assert_eq!(groups[0][1].pane.id, "%2");
+        assert_eq!(groups[1][0].session_name, "b");
+    }
+
+    #[test]
+    fn test_parse_ps_output() {
+        let map = parse_ps_output("  123 claude --resume\n 45 -zsh\n");
+        assert_eq!(map.get(&123).map(String::as_str), Some("claude --resume"));
+        assert_eq!(map.get(&45).map(String::as_str), Some("-zsh"));
+    }
+}
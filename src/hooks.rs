@@ -0,0 +1,380 @@
+//! Claude Code hook integration
+//!
+//! Claude Code runs `tsm hook <event>` on its lifecycle events. Each call
+//! records the calling pane's status (from `$TMUX_PANE`) in a small JSON
+//! file under the state directory, which discovery prefers over scraping
+//! the screen while it is fresh.
+
+use std::collections::HashSet;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config;
+use crate::session::{unix_now, ClaudeCodeStatus};
+
+/// Notification text Claude Code sends after the prompt has been idle a while
+const IDLE_NOTIFICATION: &str = "waiting for your input";
+
+/// How long, in seconds, recorded state is trusted without checking the
+/// screen. A pane whose Claude Code crashed or missed a hook would
+/// otherwise keep its last status forever.
+const FRESH_FOR: u64 = 60;
+
+/// A Claude Code hook event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    SessionStart,
+    UserPromptSubmit,
+    PreToolUse,
+    PostToolUse,
+    Notification,
+    Stop,
+    SessionEnd,
+}
+
+impl HookEvent {
+    /// Every event the installer registers
+    pub const ALL: [HookEvent; 7] = [
+        HookEvent::SessionStart,
+        HookEvent::UserPromptSubmit,
+        HookEvent::PreToolUse,
+        HookEvent::PostToolUse,
+        HookEvent::Notification,
+        HookEvent::Stop,
+        HookEvent::SessionEnd,
+    ];
+
+    /// Event name as used in Claude Code's settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::SessionStart => "SessionStart",
+            HookEvent::UserPromptSubmit => "UserPromptSubmit",
+            HookEvent::PreToolUse => "PreToolUse",
+            HookEvent::PostToolUse => "PostToolUse",
+            HookEvent::Notification => "Notification",
+            HookEvent::Stop => "Stop",
+            HookEvent::SessionEnd => "SessionEnd",
+        }
+    }
+
+    /// Argument for `tsm hook <event>`
+    pub fn arg(&self) -> &'static str {
+        match self {
+            HookEvent::SessionStart => "session-start",
+            HookEvent::UserPromptSubmit => "user-prompt-submit",
+            HookEvent::PreToolUse => "pre-tool-use",
+            HookEvent::PostToolUse => "post-tool-use",
+            HookEvent::Notification => "notification",
+            HookEvent::Stop => "stop",
+            HookEvent::SessionEnd => "session-end",
+        }
+    }
+
+    /// Whether the event is matched against tool names
+    fn uses_matcher(&self) -> bool {
+        matches!(self, HookEvent::PreToolUse | HookEvent::PostToolUse)
+    }
+}
+
+impl FromStr for HookEvent {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        HookEvent::ALL
+            .into_iter()
+            .find(|event| event.arg() == s || event.name() == s)
+            .with_context(|| format!("unknown hook event '{}'", s))
+    }
+}
+
+/// State recorded by the last hook call in a pane
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookState {
+    pub status: ClaudeCodeStatus,
+    /// Name of the event that set the status
+    pub event: String,
+    /// Unix timestamp of the hook call
+    pub updated_at: u64,
+    /// Notification text, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Tool about to run or just run, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+impl HookState {
+    /// Build the state for an event from Claude Code's JSON payload.
+    /// Returns `None` when the session ended.
+    pub fn from_event(event: HookEvent, payload: &Value, now: u64) -> Option<Self> {
+        let message = payload["message"].as_str().map(str::to_string);
+        let tool_name = payload["tool_name"].as_str().map(str::to_string);
+
+        let status = match event {
+            HookEvent::SessionStart | HookEvent::Stop => ClaudeCodeStatus::Idle,
+            HookEvent::UserPromptSubmit | HookEvent::PreToolUse | HookEvent::PostToolUse => {
+                ClaudeCodeStatus::Working
+            }
+            // Permission requests need an answer; the idle reminder does not
+            HookEvent::Notification => match message {
+                Some(ref text) if text.contains(IDLE_NOTIFICATION) => ClaudeCodeStatus::Idle,
+                _ => ClaudeCodeStatus::WaitingInput,
+            },
+            HookEvent::SessionEnd => return None,
+        };
+
+        Some(Self {
+            status,
+            event: event.name().to_string(),
+            updated_at: now,
+            message,
+            tool_name,
+        })
+    }
+
+    /// Whether the state was recorded recently enough to trust on its own
+    pub fn is_fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.updated_at) < FRESH_FOR
+    }
+}
+
+/// Directory holding one state file per pane
+fn panes_dir() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join("panes"))
+}
+
+/// State file for a pane: `%12` is stored as `12.json`
+fn state_file(dir: &Path, pane_id: &str) -> PathBuf {
+    dir.join(format!("{}.json", pane_id.trim_start_matches('%')))
+}
+
+/// Handle `tsm hook <event>`: record the state of the pane in `$TMUX_PANE`.
+/// Outside tmux there is nothing to record.
+pub fn run(event: HookEvent) -> Result<()> {
+    let Ok(pane_id) = std::env::var("TMUX_PANE") else {
+        return Ok(());
+    };
+
+    let mut input = String::new();
+    if !std::io::stdin().is_terminal() {
+        std::io::stdin().read_to_string(&mut input)?;
+    }
+    let payload = serde_json::from_str(&input).unwrap_or(Value::Null);
+
+    record(event, &pane_id, &payload)
+}
+
+/// Write (or, at session end, remove) the state file for a pane
+pub fn record(event: HookEvent, pane_id: &str, payload: &Value) -> Result<()> {
+    let Some(dir) = panes_dir() else {
+        bail!("Could not determine the state directory");
+    };
+    let path = state_file(&dir, pane_id);
+
+    let Some(state) = HookState::from_event(event, payload, unix_now()) else {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    // Write then rename so readers never see a partial file
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string(&state)?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Read the recorded state of a pane, if its hooks have run
+pub fn read_state(pane_id: &str) -> Option<HookState> {
+    let path = state_file(&panes_dir()?, pane_id);
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Remove state files of panes that no longer exist, so a reused pane ID
+/// does not inherit an old status
+pub fn prune(live_panes: &HashSet<&str>) {
+    let Some(dir) = panes_dir() else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if !live_panes.contains(format!("%{}", stem).as_str()) {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+/// Claude Code's user settings file: `$CLAUDE_CONFIG_DIR/settings.json`,
+/// falling back to `~/.claude/settings.json`
+pub fn settings_path() -> Option<PathBuf> {
+    std::env::var_os("CLAUDE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".claude")))
+        .map(|dir| dir.join("settings.json"))
+}
+
+/// Command line Claude Code should run for an event
+pub fn hook_command(program: &str, event: HookEvent) -> String {
+    if program.contains(char::is_whitespace) {
+        format!("\"{}\" hook {}", program, event.arg())
+    } else {
+        format!("{} hook {}", program, event.arg())
+    }
+}
+
+/// Add a `tsm hook` command for every event to the settings file, keeping
+/// everything else in it. Returns the events that were added; events that
+/// already run the command are left alone.
+pub fn install(settings: &Path, program: &str) -> Result<Vec<HookEvent>> {
+    let mut root: Value = if settings.exists() {
+        let text = std::fs::read_to_string(settings)
+            .with_context(|| format!("Failed to read {}", settings.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("Invalid JSON in {}", settings.display()))?
+    } else {
+        json!({})
+    };
+
+    let added = add_hooks(&mut root, program)?;
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    if let Some(dir) = settings.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut text = serde_json::to_string_pretty(&root)?;
+    text.push('\n');
+    std::fs::write(settings, text)
+        .with_context(|| format!("Failed to write {}", settings.display()))?;
+
+    Ok(added)
+}
+
+/// Add the hook entries to parsed settings
+fn add_hooks(root: &mut Value, program: &str) -> Result<Vec<HookEvent>> {
+    let Some(root) = root.as_object_mut() else {
+        bail!("settings must be a JSON object");
+    };
+    let Some(hooks) = root
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+    else {
+        bail!("\"hooks\" in settings must be an object");
+    };
+
+    let mut added = Vec::new();
+    for event in HookEvent::ALL {
+        let command = hook_command(program, event);
+        let Some(groups) = hooks
+            .entry(event.name())
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+        else {
+            bail!("\"hooks.{}\" in settings must be an array", event.name());
+        };
+
+        let installed = groups.iter().any(|group| {
+            group["hooks"]
+                .as_array()
+                .is_some_and(|list| list.iter().any(|hook| hook["command"] == command))
+        });
+        if installed {
+            continue;
+        }
+
+        let mut group = json!({ "hooks": [{ "type": "command", "command": command }] });
+        if event.uses_matcher() {
+            group["matcher"] = json!("*");
+        }
+        groups.push(group);
+        added.push(event);
+    }
+
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_event() {
+        assert_eq!("stop".parse::<HookEvent>().unwrap(), HookEvent::Stop);
+        assert_eq!(
+            "PreToolUse".parse::<HookEvent>().unwrap(),
+            HookEvent::PreToolUse
+        );
+        assert!("bogus".parse::<HookEvent>().is_err());
+    }
+
+    #[test]
+    fn test_state_from_events() {
+        let state =
+            HookState::from_event(HookEvent::PreToolUse, &json!({ "tool_name": "Bash" }), 10)
+                .unwrap();
+        assert_eq!(state.status, ClaudeCodeStatus::Working);
+        assert_eq!(state.tool_name.as_deref(), Some("Bash"));
+
+        let permission = json!({ "message": "Claude needs your permission to use Bash" });
+        let state = HookState::from_event(HookEvent::Notification, &permission, 10).unwrap();
+        assert_eq!(state.status, ClaudeCodeStatus::WaitingInput);
+
+        let idle = json!({ "message": "Claude is waiting for your input" });
+        let state = HookState::from_event(HookEvent::Notification, &idle, 10).unwrap();
+        assert_eq!(state.status, ClaudeCodeStatus::Idle);
+
+        assert!(HookState::from_event(HookEvent::SessionEnd, &Value::Null, 10).is_none());
+    }
+
+    #[test]
+    fn test_add_hooks_keeps_existing_settings() {
+        let mut settings = json!({
+            "model": "opus",
+            "hooks": {
+                "Stop": [{ "hooks": [{ "type": "command", "command": "say done" }] }]
+            }
+        });
+
+        let added = add_hooks(&mut settings, "/usr/bin/tsm").unwrap();
+        assert_eq!(added.len(), HookEvent::ALL.len());
+        assert_eq!(settings["model"], "opus");
+        assert_eq!(settings["hooks"]["Stop"].as_array().unwrap().len(), 2);
+        assert_eq!(settings["hooks"]["PreToolUse"][0]["matcher"], "*");
+        assert_eq!(
+            settings["hooks"]["Stop"][1]["hooks"][0]["command"],
+            "/usr/bin/tsm hook stop"
+        );
+
+        // Installing again changes nothing
+        assert!(add_hooks(&mut settings, "/usr/bin/tsm").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_state_file_name() {
+        let path = state_file(Path::new("/state"), "%12");
+        assert_eq!(path, PathBuf::from("/state/12.json"));
+    }
+}
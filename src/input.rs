@@ -1,6 +1,6 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-use crate::app::{App, Mode, NewSessionField};
+use crate::app::{App, Mode, NewSessionField, SessionAction};
 
 /// Handle a key event and update the application state
 pub fn handle_key(app: &mut App, key: KeyEvent) {
@@ -14,6 +14,19 @@ pub fn handle_key(app: &mut App, key: KeyEvent) {
         Mode::ConfirmAction => handle_confirm_action_mode(app, key),
         Mode::NewSession { .. } => handle_new_session_mode(app, key),
         Mode::Rename { .. } => handle_rename_mode(app, key),
+        Mode::QuickReply { .. } => handle_quick_reply_mode(app, key),
+        Mode::SendText { .. } => handle_send_text_mode(app, key),
+        Mode::ConfirmBroadcast { .. } => handle_confirm_broadcast_mode(app, key),
+        Mode::BulkRename { .. } => handle_bulk_rename_mode(app, key),
+        Mode::ConfirmBulk { .. } => handle_confirm_bulk_mode(app, key),
+        Mode::BulkResults { .. } => app.cancel(),
+        Mode::Queue { .. } => handle_queue_mode(app, key),
+        Mode::Tree { .. } => handle_tree_mode(app, key),
+        Mode::NewWindow { .. } => handle_new_window_mode(app, key),
+        Mode::RenameWindow { .. } => handle_rename_window_mode(app, key),
+        Mode::PickSession { .. } => handle_pick_session_mode(app, key),
+        Mode::Preview { search: Some(_) } => handle_pager_search_mode(app, key),
+        Mode::Preview { search: None } => handle_pager_mode(app, key),
         Mode::Help => handle_help_mode(app, key),
     }
 }
@@ -58,6 +71,56 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
             app.start_kill();
         }
 
+        // Answer the prompt a waiting agent shows
+        KeyCode::Char('a') => {
+            app.start_quick_reply();
+        }
+
+        // Send text to the agent
+        KeyCode::Char('s') => {
+            app.start_send_text();
+        }
+
+        // Send text to several sessions
+        KeyCode::Char('B') => {
+            app.start_broadcast();
+        }
+
+        // Marking for bulk actions
+        KeyCode::Char('m') => {
+            app.toggle_mark();
+            app.select_next();
+        }
+        KeyCode::Char('M') => {
+            app.mark_group();
+        }
+        KeyCode::Char('*') => {
+            app.mark_filtered();
+        }
+        KeyCode::Char('u') => {
+            app.clear_marks();
+        }
+
+        // Detach clients
+        KeyCode::Char('d') => {
+            app.start_detach();
+        }
+
+        // Prompt queue
+        KeyCode::Char('Q') => {
+            app.start_queue();
+        }
+
+        // Browse windows and panes
+        KeyCode::Char('t') => {
+            app.start_tree();
+        }
+
+        // Read the pane's history
+        KeyCode::Char('v') => {
+            app.open_pager();
+        }
+
         // Rename session
         KeyCode::Char('r') => {
             app.start_rename();
@@ -78,6 +141,11 @@ fn handle_normal_mode(app: &mut App, key: KeyEvent) {
             app.refresh();
         }
 
+        // Cycle sort mode
+        KeyCode::Char('o') => {
+            app.cycle_sort();
+        }
+
         // Toggle preview
         KeyCode::Char('p') => {
             app.show_preview = !app.show_preview;
@@ -134,6 +202,11 @@ fn handle_action_menu_mode(app: &mut App, key: KeyEvent) {
             app.execute_selected_action();
         }
 
+        // Browse windows and panes
+        KeyCode::Char('t') => {
+            app.start_tree();
+        }
+
         // Back to session list
         KeyCode::Char('h') | KeyCode::Left | KeyCode::Esc => {
             app.cancel();
@@ -148,6 +221,197 @@ fn handle_action_menu_mode(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_tree_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.move_tree_selection(1);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.move_tree_selection(-1);
+        }
+        KeyCode::Char('g') | KeyCode::Home => {
+            app.move_tree_selection(isize::MIN);
+        }
+        KeyCode::Char('G') | KeyCode::End => {
+            app.move_tree_selection(isize::MAX);
+        }
+
+        // Switch to the window or pane and quit
+        KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => {
+            app.switch_to_tree_node(false);
+        }
+
+        // Switch but keep tsm open
+        KeyCode::Char(' ') => {
+            app.switch_to_tree_node(true);
+        }
+
+        // Window and pane actions
+        KeyCode::Char('n') => {
+            app.start_new_window();
+        }
+        KeyCode::Char('x') | KeyCode::Char('K') => {
+            app.start_tree_kill();
+        }
+        KeyCode::Char('r') => {
+            app.start_tree_action(SessionAction::RenameWindow);
+        }
+        KeyCode::Char('m') => {
+            app.start_tree_action(SessionAction::MoveWindow);
+        }
+        KeyCode::Char('b') => {
+            app.start_tree_action(SessionAction::BreakPane);
+        }
+        KeyCode::Char('J') => {
+            app.start_tree_action(SessionAction::JoinPane);
+        }
+        KeyCode::Char('v') => {
+            app.open_pager();
+        }
+
+        KeyCode::Char('h') | KeyCode::Left | KeyCode::Esc | KeyCode::Char('t') => {
+            app.cancel();
+        }
+        KeyCode::Char('q') => {
+            app.should_quit = true;
+        }
+        _ => {}
+    }
+}
+
+fn handle_new_window_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel();
+        }
+        KeyCode::Enter => {
+            app.confirm_new_window();
+        }
+        KeyCode::Tab => {
+            if let Mode::NewWindow {
+                ref mut start_claude,
+                ..
+            } = app.mode
+            {
+                *start_claude = !*start_claude;
+            }
+        }
+        KeyCode::Backspace => {
+            if let Mode::NewWindow { ref mut name, .. } = app.mode {
+                name.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Mode::NewWindow { ref mut name, .. } = app.mode {
+                name.push(c);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_rename_window_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel();
+        }
+        KeyCode::Enter => {
+            app.confirm_rename_window();
+        }
+        KeyCode::Backspace => {
+            if let Mode::RenameWindow { ref mut new_name } = app.mode {
+                new_name.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Mode::RenameWindow { ref mut new_name } = app.mode {
+                new_name.push(c);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_pick_session_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.move_destination_selection(1);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.move_destination_selection(-1);
+        }
+        KeyCode::Enter => {
+            app.confirm_pick_session();
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.cancel();
+        }
+        _ => {}
+    }
+}
+
+fn handle_pager_mode(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('v') => {
+            app.close_pager();
+            return;
+        }
+        KeyCode::Char('/') => {
+            app.start_pager_search();
+            return;
+        }
+        _ => {}
+    }
+
+    let Some(ref mut pager) = app.pager else {
+        return;
+    };
+    match key.code {
+        KeyCode::Char('d') if ctrl => pager.page_half(1),
+        KeyCode::Char('u') if ctrl => pager.page_half(-1),
+        KeyCode::Char('f') if ctrl => pager.page(1),
+        KeyCode::Char('b') if ctrl => pager.page(-1),
+        KeyCode::Char('j') | KeyCode::Down => pager.scroll(1),
+        KeyCode::Char('k') | KeyCode::Up => pager.scroll(-1),
+        KeyCode::PageDown | KeyCode::Char(' ') => pager.page(1),
+        KeyCode::PageUp => pager.page(-1),
+        KeyCode::Char('g') | KeyCode::Home => pager.scroll_to_top(),
+        KeyCode::Char('G') | KeyCode::End => pager.scroll_to_bottom(),
+        KeyCode::Char('n') => pager.next_match(true),
+        KeyCode::Char('N') => pager.next_match(false),
+        _ => {}
+    }
+}
+
+fn handle_pager_search_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = Mode::Preview { search: None };
+        }
+        KeyCode::Enter => {
+            app.confirm_pager_search();
+        }
+        KeyCode::Backspace => {
+            if let Mode::Preview {
+                search: Some(ref mut query),
+            } = app.mode
+            {
+                query.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Mode::Preview {
+                search: Some(ref mut query),
+            } = app.mode
+            {
+                query.push(c);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn handle_confirm_action_mode(app: &mut App, key: KeyEvent) {
     match key.code {
         KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
@@ -296,6 +560,189 @@ fn handle_rename_mode(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_quick_reply_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.move_quick_reply(1);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.move_quick_reply(-1);
+        }
+        KeyCode::Enter => {
+            app.send_quick_reply();
+        }
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            app.type_quick_reply(c);
+        }
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.cancel();
+        }
+        _ => {}
+    }
+}
+
+fn handle_send_text_mode(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel();
+        }
+        // New line: Alt+Enter, or Ctrl+J where the terminal can't send it
+        KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+            app.push_send_text("\n");
+        }
+        KeyCode::Char('j') if ctrl => {
+            app.push_send_text("\n");
+        }
+        KeyCode::Enter => {
+            app.send_text();
+        }
+        KeyCode::Tab => {
+            app.cycle_broadcast_scope();
+        }
+        KeyCode::Char('e') if ctrl => {
+            if let Mode::SendText { ref mut enter, .. } = app.mode {
+                *enter = !*enter;
+            }
+        }
+        KeyCode::Char('l') if ctrl => {
+            if let Mode::SendText {
+                ref mut literal, ..
+            } = app.mode
+            {
+                *literal = !*literal;
+            }
+        }
+        KeyCode::Backspace => {
+            if let Mode::SendText { ref mut text, .. } = app.mode {
+                text.pop();
+            }
+        }
+        KeyCode::Char(c) if !ctrl => {
+            app.push_send_text(c.encode_utf8(&mut [0; 4]));
+        }
+        _ => {}
+    }
+}
+
+fn handle_bulk_rename_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel();
+        }
+        KeyCode::Enter => {
+            app.confirm_bulk_rename();
+        }
+        KeyCode::Backspace => {
+            if let Mode::BulkRename { ref mut pattern } = app.mode {
+                pattern.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Mode::BulkRename { ref mut pattern } = app.mode {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '{' | '}') {
+                    pattern.push(c);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_confirm_bulk_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.confirm_bulk();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.cancel();
+        }
+        _ => {}
+    }
+}
+
+fn handle_confirm_broadcast_mode(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.confirm_broadcast();
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.cancel();
+        }
+        _ => {}
+    }
+}
+
+fn handle_queue_mode(app: &mut App, key: KeyEvent) {
+    // Typing a new prompt
+    if let Mode::Queue {
+        input: Some(ref mut input),
+        ..
+    } = app.mode
+    {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                app.cancel_queue_input();
+            }
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+                input.push('\n');
+            }
+            KeyCode::Char('j') if ctrl => {
+                input.push('\n');
+            }
+            KeyCode::Enter => {
+                app.add_queued();
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                input.push(c);
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.move_queue_selection(1);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.move_queue_selection(-1);
+        }
+        KeyCode::Char('J') => {
+            app.reorder_queued(true);
+        }
+        KeyCode::Char('K') => {
+            app.reorder_queued(false);
+        }
+        KeyCode::Char('a') | KeyCode::Char('i') => {
+            app.start_queue_input();
+        }
+        KeyCode::Char('d') | KeyCode::Char('x') | KeyCode::Delete => {
+            app.delete_queued();
+        }
+        KeyCode::Char('p') => {
+            app.toggle_queue_paused();
+        }
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.cancel();
+        }
+        _ => {}
+    }
+}
+
+/// Handle text pasted into the terminal
+pub fn handle_paste(app: &mut App, text: &str) {
+    match app.mode {
+        Mode::SendText { .. } => app.push_send_text(text),
+        Mode::Queue { .. } => app.push_queue_input(text),
+        _ => {}
+    }
+}
+
 fn handle_help_mode(app: &mut App, key: KeyEvent) {
     match key.code {
         KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('?') => {
@@ -1,17 +1,33 @@
+mod agents;
+mod api;
 mod app;
+mod cli;
 mod completion;
+mod config;
+mod control;
+mod daemon;
 mod detection;
+mod discovery;
+mod hooks;
 mod input;
+mod mcp;
+mod notify;
+mod output;
+mod pager;
+mod queue;
 mod scroll_state;
 mod session;
+mod status_line;
 mod tmux;
 mod ui;
+mod watch;
+mod worker;
 
 use std::io::{self, stdout};
 
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event},
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
@@ -20,9 +36,16 @@ use ratatui::prelude::*;
 use crate::app::App;
 
 fn main() -> Result<()> {
+    // Run a subcommand without touching the terminal, if one was given
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = cli::parse(&args)? {
+        return cli::run(command);
+    }
+
     // Set up terminal
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
+    stdout().execute(EnableBracketedPaste)?;
 
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
@@ -32,6 +55,7 @@ fn main() -> Result<()> {
 
     // Restore terminal
     disable_raw_mode()?;
+    stdout().execute(DisableBracketedPaste)?;
     stdout().execute(LeaveAlternateScreen)?;
 
     result
@@ -41,7 +65,7 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
     let mut app = App::new()?;
 
     loop {
-        // Refresh sessions and preview every tick
+        // Apply the latest background refresh, if any
         app.tick();
 
         // Draw the UI
@@ -54,8 +78,10 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
 
         // Handle events
         if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                input::handle_key(&mut app, key);
+            match event::read()? {
+                Event::Key(key) => input::handle_key(&mut app, key),
+                Event::Paste(text) => input::handle_paste(&mut app, &text),
+                _ => {}
             }
         }
     }
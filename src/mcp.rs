@@ -0,0 +1,406 @@
+//! Model Context Protocol server on stdin/stdout
+//!
+//! `tsm mcp` lets a coordinating agent manage sibling sessions: list them,
+//! read their recent output, start new ones and send them prompts. Messages
+//! are JSON-RPC 2.0, one per line. Destructive tools are only offered when
+//! they are listed in the `[mcp] allow` setting, which is empty by default.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+
+use crate::app::App;
+use crate::config::{Config, McpConfig};
+use crate::detection::strip_ansi;
+use crate::output::{self, OutputFormat};
+use crate::tmux::Tmux;
+
+/// Protocol versions this server speaks, newest first. A client asking for
+/// another version is offered the newest.
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Lines returned by `capture_pane` by default
+const DEFAULT_CAPTURE_LINES: usize = 50;
+
+/// Tools that change or end what runs in a session
+pub const DESTRUCTIVE_TOOLS: [&str; 2] = ["send_keys", "kill_session"];
+
+/// JSON-RPC error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+
+/// Serve MCP requests until stdin closes
+pub fn run() -> Result<()> {
+    let server = Server::new(Config::load()?.mcp);
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle(&message),
+            Err(e) => Some(error_response(
+                Value::Null,
+                PARSE_ERROR,
+                &format!("Parse error: {}", e),
+            )),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Handles MCP messages with a fixed set of allowed tools
+struct Server {
+    config: McpConfig,
+}
+
+impl Server {
+    fn new(config: McpConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether a tool may be listed and called
+    fn is_allowed(&self, tool: &str) -> bool {
+        !DESTRUCTIVE_TOOLS.contains(&tool) || self.config.allow.iter().any(|t| t == tool)
+    }
+
+    /// Answer one message. Notifications (messages without an `id`) get
+    /// no response.
+    fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": protocol_version(
+                    params.get("protocolVersion").and_then(Value::as_str)
+                ),
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "tsm", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.tools() })),
+            "tools/call" => self.call_tool(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    /// Descriptions of the allowed tools
+    fn tools(&self) -> Vec<Value> {
+        tool_definitions()
+            .into_iter()
+            .filter(|tool| self.is_allowed(tool["name"].as_str().unwrap_or("")))
+            .collect()
+    }
+
+    /// Run a tool. Failures of the tool itself are reported in the result
+    /// with `isError` so the agent can read them.
+    fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        if !tool_definitions().iter().any(|tool| tool["name"] == name) {
+            return Err((INVALID_PARAMS, format!("Unknown tool '{}'", name)));
+        }
+        let outcome = if self.is_allowed(name) {
+            run_tool(name, &arguments)
+        } else {
+            Err(anyhow!(
+                "Tool '{}' is not allowed; add it to [mcp] allow in the tsm config",
+                name
+            ))
+        };
+
+        Ok(match outcome {
+            Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+            Err(e) => json!({
+                "content": [{ "type": "text", "text": format!("{:#}", e) }],
+                "isError": true,
+            }),
+        })
+    }
+}
+
+/// The version to answer `initialize` with: the client's if we speak it,
+/// otherwise our newest
+fn protocol_version(requested: Option<&str>) -> &'static str {
+    PROTOCOL_VERSIONS
+        .into_iter()
+        .find(|version| Some(*version) == requested)
+        .unwrap_or(PROTOCOL_VERSIONS[0])
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Names, descriptions and input schemas of every tool
+fn tool_definitions() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "list_sessions",
+            "description": "List tmux sessions with their working directory, panes and agent status (idle, working, input, unknown).",
+            "inputSchema": { "type": "object", "properties": {} },
+        }),
+        json!({
+            "name": "capture_pane",
+            "description": "Read the last lines of a session's agent pane (or its first pane).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": { "type": "string", "description": "Session name or ID, or a pane ID like %3" },
+                    "lines": { "type": "integer", "description": "Number of lines, default 50" },
+                },
+                "required": ["session"],
+            },
+        }),
+        json!({
+            "name": "new_session",
+            "description": "Create a detached tmux session in a directory, starting Claude Code in it unless start_claude is false. Returns the session name.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Working directory" },
+                    "name": { "type": "string", "description": "Session name, generated when omitted" },
+                    "start_claude": { "type": "boolean", "description": "Start Claude Code, default true" },
+                },
+                "required": ["path"],
+            },
+        }),
+        json!({
+            "name": "send_keys",
+            "description": "Type text into a session's agent pane and press Enter, e.g. to send a prompt. Use keys for tmux key names like Escape or Down.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": { "type": "string", "description": "Session name or ID, or a pane ID like %3" },
+                    "text": { "type": "string", "description": "Text typed literally" },
+                    "enter": { "type": "boolean", "description": "Press Enter after the text, default true" },
+                    "keys": { "type": "array", "items": { "type": "string" }, "description": "tmux key names sent after the text" },
+                },
+                "required": ["session"],
+            },
+        }),
+        json!({
+            "name": "kill_session",
+            "description": "Kill a tmux session and everything running in it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": { "type": "string", "description": "Session name or ID" },
+                },
+                "required": ["session"],
+            },
+        }),
+    ]
+}
+
+/// Run a tool and describe its result as text
+fn run_tool(name: &str, arguments: &Value) -> Result<String> {
+    match name {
+        "list_sessions" => output::render_sessions(&Tmux::list_sessions()?, OutputFormat::Json),
+        "capture_pane" => {
+            let pane = resolve_pane(string_arg(arguments, "session")?)?;
+            let lines = arguments
+                .get("lines")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_CAPTURE_LINES, |n| n as usize);
+            Ok(strip_ansi(&Tmux::capture_pane(&pane, lines, false)?))
+        }
+        "new_session" => {
+            let path = PathBuf::from(string_arg(arguments, "path")?);
+            if !path.is_dir() {
+                bail!("{} is not a directory", path.display());
+            }
+            let start_claude = arguments
+                .get("start_claude")
+                .and_then(Value::as_bool)
+                .unwrap_or(true);
+            let name = match arguments.get("name").and_then(Value::as_str) {
+                Some(name) => name.to_string(),
+                None => App::generate_session_name(start_claude),
+            };
+            Tmux::new_session(&name, &path, start_claude)?;
+            Ok(name)
+        }
+        "send_keys" => {
+            let pane = resolve_pane(string_arg(arguments, "session")?)?;
+            let text = arguments.get("text").and_then(Value::as_str);
+            let keys: Vec<String> = arguments
+                .get("keys")
+                .and_then(Value::as_array)
+                .map(|keys| {
+                    keys.iter()
+                        .filter_map(|k| k.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            if text.is_none() && keys.is_empty() {
+                bail!("Nothing to send: give text or keys");
+            }
+
+            if let Some(text) = text {
+                Tmux::send_keys(&pane, &[text.to_string()], true)?;
+                if arguments
+                    .get("enter")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true)
+                {
+                    Tmux::send_keys(&pane, &["Enter".to_string()], false)?;
+                }
+            }
+            if !keys.is_empty() {
+                Tmux::send_keys(&pane, &keys, false)?;
+            }
+            Ok(format!("Sent to {}", pane))
+        }
+        "kill_session" => {
+            let key = string_arg(arguments, "session")?;
+            let sessions = Tmux::list_sessions()?;
+            let session = sessions
+                .iter()
+                .find(|s| s.name == key || s.id == key)
+                .with_context(|| format!("No session '{}'", key))?;
+            Tmux::kill_session(&session.id)?;
+            Ok(format!("Killed session '{}'", session.name))
+        }
+        _ => bail!("Unknown tool '{}'", name),
+    }
+}
+
+fn string_arg<'a>(arguments: &'a Value, key: &str) -> Result<&'a str> {
+    arguments
+        .get(key)
+        .and_then(Value::as_str)
+        .with_context(|| format!("Missing argument '{}'", key))
+}
+
+/// Pane to read from or type into: a pane ID as given, or the agent pane
+/// of the named session. Sessions not listed yet (e.g. just created) are
+/// left to tmux, which picks their active pane.
+fn resolve_pane(target: &str) -> Result<String> {
+    if target.starts_with('%') {
+        return Ok(target.to_string());
+    }
+    let sessions = Tmux::list_sessions()?;
+    Ok(sessions
+        .iter()
+        .find(|s| s.name == target || s.id == target)
+        .and_then(|s| s.preview_pane())
+        .map(str::to_string)
+        .unwrap_or_else(|| Tmux::session_target(target)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_names(server: &Server) -> Vec<String> {
+        server
+            .tools()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_initialize() {
+        let server = Server::new(McpConfig::default());
+        let response = server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2025-03-26" },
+            }))
+            .unwrap();
+        assert_eq!(response["id"], json!(1));
+        assert_eq!(response["result"]["protocolVersion"], json!("2025-03-26"));
+        assert_eq!(response["result"]["serverInfo"]["name"], json!("tsm"));
+
+        let response = server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "initialize",
+                "params": { "protocolVersion": "2099-01-01" },
+            }))
+            .unwrap();
+        assert_eq!(
+            response["result"]["protocolVersion"],
+            json!(PROTOCOL_VERSIONS[0])
+        );
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        let server = Server::new(McpConfig::default());
+        assert!(server
+            .handle(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .is_none());
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let server = Server::new(McpConfig::default());
+        assert_eq!(
+            tool_names(&server),
+            ["list_sessions", "capture_pane", "new_session"]
+        );
+
+        let response = server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "kill_session", "arguments": { "session": "work" } },
+            }))
+            .unwrap();
+        assert_eq!(response["result"]["isError"], json!(true));
+
+        let server = Server::new(McpConfig {
+            allow: vec!["kill_session".to_string()],
+        });
+        assert!(tool_names(&server).contains(&"kill_session".to_string()));
+        assert!(!tool_names(&server).contains(&"send_keys".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_tool_and_method() {
+        let server = Server::new(McpConfig::default());
+        let response = server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": { "name": "format_disk" },
+            }))
+            .unwrap();
+        assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+
+        let response = server
+            .handle(&json!({ "jsonrpc": "2.0", "id": 4, "method": "resources/list" }))
+            .unwrap();
+        assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+    }
+}
@@ -0,0 +1,271 @@
+//! Notifications when an agent needs attention
+//!
+//! A notification is sent when an agent pane starts waiting for input, or
+//! finishes working and goes idle. Each configured backend delivers it;
+//! failures are ignored since a missed notification is not worth an error.
+
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::config::NotificationConfig;
+use crate::session::{ClaudeCodeStatus, Session};
+
+/// How a notification is delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// Desktop notification via `notify-send`
+    NotifySend,
+    /// OSC 9 terminal notification (iTerm2, WezTerm, kitty, ...)
+    Osc9,
+    /// OSC 777 terminal notification (rxvt, foot, Ghostty, ...)
+    Osc777,
+    /// `tmux display-message` on the current client
+    Tmux,
+    /// Terminal bell
+    Bell,
+}
+
+/// An agent pane whose status changed in a way the user should hear about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub session_id: String,
+    pub session_name: String,
+    /// Window of the pane, when the session has more than one agent pane
+    pub window_index: Option<u32>,
+    pub status: ClaudeCodeStatus,
+}
+
+impl Notification {
+    pub fn title(&self) -> String {
+        format!("tsm: {}", self.session_name)
+    }
+
+    pub fn body(&self) -> String {
+        let text = match self.status {
+            ClaudeCodeStatus::WaitingInput => "Waiting for input",
+            _ => "Finished working",
+        };
+        match self.window_index {
+            Some(index) => format!("{} (window {})", text, index),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Whether a status change is worth a notification
+pub fn is_notable(old: ClaudeCodeStatus, new: ClaudeCodeStatus) -> bool {
+    match new {
+        ClaudeCodeStatus::WaitingInput => old != ClaudeCodeStatus::WaitingInput,
+        ClaudeCodeStatus::Idle => old == ClaudeCodeStatus::Working,
+        _ => false,
+    }
+}
+
+/// Status of every agent pane, keyed by pane ID
+pub fn pane_statuses(sessions: &[Session]) -> HashMap<String, ClaudeCodeStatus> {
+    sessions
+        .iter()
+        .flat_map(|s| s.agent_panes())
+        .map(|p| (p.id.clone(), p.status))
+        .collect()
+}
+
+/// Notable changes from `before` (see [`pane_statuses`]) to `sessions`.
+/// Panes not seen before and muted sessions are skipped.
+pub fn changes(
+    before: &HashMap<String, ClaudeCodeStatus>,
+    sessions: &[Session],
+) -> Vec<Notification> {
+    let mut notifications = Vec::new();
+
+    for session in sessions.iter().filter(|s| !s.muted) {
+        let several = session.agent_panes().count() > 1;
+        for pane in session.agent_panes() {
+            let Some(&old) = before.get(&pane.id) else {
+                continue;
+            };
+            if is_notable(old, pane.status) {
+                notifications.push(Notification {
+                    session_id: session.id.clone(),
+                    session_name: session.name.clone(),
+                    window_index: several.then_some(pane.window_index),
+                    status: pane.status,
+                });
+            }
+        }
+    }
+
+    notifications
+}
+
+/// Sends notifications, at most one per session per debounce interval
+pub struct Notifier {
+    config: NotificationConfig,
+    last_sent: HashMap<String, Instant>,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationConfig) -> Self {
+        Self {
+            config,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Deliver notifications through every configured backend
+    pub fn notify(&mut self, notifications: Vec<Notification>) {
+        if !self.config.enabled {
+            return;
+        }
+        let debounce = Duration::from_secs(self.config.debounce_secs);
+
+        for notification in notifications {
+            if self
+                .last_sent
+                .get(&notification.session_id)
+                .is_some_and(|sent| sent.elapsed() < debounce)
+            {
+                continue;
+            }
+            self.last_sent
+                .insert(notification.session_id.clone(), Instant::now());
+
+            for backend in &self.config.backends {
+                send(*backend, &notification);
+            }
+        }
+    }
+}
+
+/// Deliver one notification through one backend
+fn send(backend: Backend, notification: &Notification) {
+    let title = notification.title();
+    let body = notification.body();
+
+    match backend {
+        Backend::NotifySend => {
+            spawn_detached(Command::new("notify-send").args(["--app-name=tsm", &title, &body]))
+        }
+        Backend::Tmux => spawn_detached(
+            Command::new("tmux").args(["display-message", &format!("{}: {}", title, body)]),
+        ),
+        Backend::Osc9 => write_terminal(&format!("\x1b]9;{}: {}\x07", title, body)),
+        Backend::Osc777 => write_terminal(&format!("\x1b]777;notify;{};{}\x07", title, body)),
+        Backend::Bell => write_terminal("\x07"),
+    }
+}
+
+/// Run a command in the background without waiting for it
+fn spawn_detached(command: &mut Command) {
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    // Reap the child off the calling thread
+    if let Ok(mut child) = child {
+        std::thread::spawn(move || child.wait());
+    }
+}
+
+/// Write an escape sequence to the terminal, wrapped for tmux passthrough
+/// when running inside tmux
+fn write_terminal(sequence: &str) {
+    let mut stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        return;
+    }
+    let _ = stdout.write_all(passthrough(sequence, std::env::var_os("TMUX").is_some()).as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Wrap an escape sequence in tmux's DCS passthrough, which needs every
+/// ESC doubled. The bell needs no wrapping.
+fn passthrough(sequence: &str, inside_tmux: bool) -> String {
+    if !inside_tmux || !sequence.starts_with('\x1b') {
+        return sequence.to_string();
+    }
+    format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::fixtures::{pane, session, session_with_panes};
+
+    #[test]
+    fn test_is_notable() {
+        use ClaudeCodeStatus::*;
+        assert!(is_notable(Working, WaitingInput));
+        assert!(is_notable(Idle, WaitingInput));
+        assert!(is_notable(Working, Idle));
+        assert!(!is_notable(WaitingInput, WaitingInput));
+        assert!(!is_notable(WaitingInput, Idle));
+        assert!(!is_notable(Idle, Working));
+    }
+
+    #[test]
+    fn test_changes() {
+        let before = pane_statuses(&[
+            session("$1", ClaudeCodeStatus::Working),
+            Session {
+                muted: true,
+                ..session("$2", ClaudeCodeStatus::Working)
+            },
+        ]);
+        let after = [
+            session_with_panes(
+                "$1",
+                vec![
+                    pane("%1", Some(ClaudeCodeStatus::WaitingInput)),
+                    pane("%3", Some(ClaudeCodeStatus::WaitingInput)),
+                ],
+            ),
+            Session {
+                muted: true,
+                ..session("$2", ClaudeCodeStatus::Idle)
+            },
+        ];
+
+        // %3 is new and $2 is muted
+        let notifications = changes(&before, &after);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].session_id, "$1");
+        assert_eq!(notifications[0].window_index, Some(0));
+        assert_eq!(notifications[0].body(), "Waiting for input (window 0)");
+    }
+
+    #[test]
+    fn test_debounce_per_session() {
+        let mut notifier = Notifier::new(NotificationConfig {
+            backends: Vec::new(),
+            ..NotificationConfig::default()
+        });
+        let notification = Notification {
+            session_id: "$1".to_string(),
+            session_name: "work".to_string(),
+            window_index: None,
+            status: ClaudeCodeStatus::Idle,
+        };
+
+        notifier.notify(vec![notification.clone()]);
+        let first = notifier.last_sent["$1"];
+        notifier.notify(vec![notification]);
+        assert_eq!(notifier.last_sent["$1"], first);
+    }
+
+    #[test]
+    fn test_passthrough() {
+        assert_eq!(passthrough("\x1b]9;hi\x07", false), "\x1b]9;hi\x07");
+        assert_eq!(
+            passthrough("\x1b]9;hi\x07", true),
+            "\x1bPtmux;\x1b\x1b]9;hi\x07\x1b\\"
+        );
+        assert_eq!(passthrough("\x07", true), "\x07");
+    }
+}
@@ -0,0 +1,172 @@
+//! Session list output formats for the CLI
+//!
+//! The JSON, NDJSON and TSV formats are a stable interface for scripts.
+//! Fields are only ever added, never renamed or removed. See the README
+//! for the full schema.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+
+use crate::session::Session;
+
+/// How `tsm list` prints sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Aligned, human-readable table
+    #[default]
+    Table,
+    /// A single JSON array of session objects
+    Json,
+    /// One JSON session object per line
+    Ndjson,
+    /// One tab-separated row per session, no header
+    Tsv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "tsv" => Ok(Self::Tsv),
+            other => bail!(
+                "unknown format '{}' (expected table, json, ndjson or tsv)",
+                other
+            ),
+        }
+    }
+}
+
+/// Render sessions in the given format. The result ends with a newline
+/// unless it is empty.
+pub fn render_sessions(sessions: &[Session], format: OutputFormat) -> Result<String> {
+    let mut out = String::new();
+
+    match format {
+        OutputFormat::Table => {
+            let name_width = sessions
+                .iter()
+                .map(|s| s.name.len())
+                .max()
+                .unwrap_or(0)
+                .max(4);
+            let status_width = sessions
+                .iter()
+                .map(|s| s.status_summary().len())
+                .max()
+                .unwrap_or(0)
+                .max(8);
+
+            for session in sessions {
+                let attached = if session.attached { "*" } else { " " };
+                out.push_str(&format!(
+                    "{} {:<name_width$}  {:<status_width$}  {:>2}w  {}\n",
+                    attached,
+                    session.name,
+                    session.status_summary(),
+                    session.window_count,
+                    session.display_path(),
+                    name_width = name_width,
+                    status_width = status_width,
+                ));
+            }
+        }
+        OutputFormat::Json => {
+            out.push_str(&serde_json::to_string_pretty(sessions)?);
+            out.push('\n');
+        }
+        OutputFormat::Ndjson => {
+            for session in sessions {
+                out.push_str(&serde_json::to_string(session)?);
+                out.push('\n');
+            }
+        }
+        OutputFormat::Tsv => {
+            for session in sessions {
+                let fields = [
+                    tsv_field(&session.name),
+                    session.created.to_string(),
+                    u8::from(session.attached).to_string(),
+                    tsv_field(&session.working_directory.to_string_lossy()),
+                    session.window_count.to_string(),
+                    session.panes.len().to_string(),
+                    tsv_field(session.claude_code_pane.as_deref().unwrap_or("")),
+                    session.claude_code_status.label().to_string(),
+                    tsv_field(&session.pane_title),
+                ];
+                out.push_str(&fields.join("\t"));
+                out.push('\n');
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Replace characters that would break a TSV row
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::session::fixtures::{pane, session_with_panes};
+    use crate::session::{ClaudeCodeStatus, Pane};
+
+    fn sample() -> Session {
+        let pane = Pane {
+            window_index: 1,
+            current_path: PathBuf::from("/src/app"),
+            pid: 42,
+            title: "fix\tbug".to_string(),
+            ..pane("%3", Some(ClaudeCodeStatus::WaitingInput))
+        };
+        Session {
+            created: 1700000000,
+            attached: true,
+            working_directory: PathBuf::from("/src/app"),
+            window_count: 2,
+            pane_title: pane.title.clone(),
+            ..session_with_panes("$1", vec![pane])
+        }
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            "ndjson".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Ndjson
+        );
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_tsv_row() {
+        let out = render_sessions(&[sample()], OutputFormat::Tsv).unwrap();
+        assert_eq!(
+            out,
+            "work\t1700000000\t1\t/src/app\t2\t1\t%3\tinput\tfix bug\n"
+        );
+    }
+
+    #[test]
+    fn test_ndjson_schema() {
+        let out = render_sessions(&[sample()], OutputFormat::Ndjson).unwrap();
+        let value: serde_json::Value = serde_json::from_str(out.trim_end()).unwrap();
+        assert_eq!(value["id"], "$1");
+        assert_eq!(value["name"], "work");
+        assert_eq!(value["attached"], true);
+        assert_eq!(value["working_directory"], "/src/app");
+        assert_eq!(value["agent"], "claude");
+        assert_eq!(value["claude_code_status"], "input");
+        assert_eq!(value["panes"][0]["id"], "%3");
+        assert_eq!(value["panes"][0]["pid"], 42);
+    }
+}
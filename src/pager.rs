@@ -0,0 +1,306 @@
+//! Scrollable, searchable view of a pane's history
+
+use ansi_to_tui::IntoText;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+
+/// A search hit, in characters of one line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Match {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+/// A pane's captured history with a scroll position and search
+pub struct Pager {
+    /// What is shown, e.g. `work:0.%3`
+    pub title: String,
+    /// Lines with the pane's colors
+    lines: Vec<Line<'static>>,
+    /// Plain text of each line, for searching
+    text: Vec<Vec<char>>,
+    /// First line on screen
+    top: usize,
+    /// Lines that fit on screen, set when rendering
+    height: usize,
+    /// Last search; empty when not searching
+    pub query: String,
+    matches: Vec<Match>,
+    /// Index of the highlighted match
+    current: Option<usize>,
+}
+
+impl Pager {
+    /// Build a pager over captured pane content, scrolled to the end
+    pub fn new(title: String, content: &str) -> Self {
+        let lines = match content.into_text() {
+            Ok(text) => text.lines,
+            Err(_) => Text::raw(content.to_string()).lines,
+        };
+        let text = lines
+            .iter()
+            .map(|line| line.spans.iter().flat_map(|s| s.content.chars()).collect())
+            .collect();
+        Self {
+            title,
+            lines,
+            text,
+            top: usize::MAX,
+            height: 0,
+            query: String::new(),
+            matches: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// First line on screen
+    pub fn top(&self) -> usize {
+        self.top.min(self.max_top())
+    }
+
+    fn max_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.height)
+    }
+
+    /// Fit the view to `height` lines
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height;
+        self.top = self.top();
+    }
+
+    /// Scroll by `delta` lines
+    pub fn scroll(&mut self, delta: isize) {
+        self.top = self.top().saturating_add_signed(delta).min(self.max_top());
+    }
+
+    /// Scroll by `pages` screens
+    pub fn page(&mut self, pages: isize) {
+        self.scroll(pages.saturating_mul(self.height.max(1) as isize));
+    }
+
+    /// Scroll by half a screen per step
+    pub fn page_half(&mut self, steps: isize) {
+        self.scroll(steps.saturating_mul((self.height / 2).max(1) as isize));
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.top = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.top = self.max_top();
+    }
+
+    /// Find every occurrence of `query` and jump to the first one on or
+    /// below the screen. Lowercase queries ignore case.
+    pub fn search(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.matches.clear();
+        self.current = None;
+        if query.is_empty() {
+            return;
+        }
+
+        let ignore_case = !query.chars().any(char::is_uppercase);
+        let needle: Vec<char> = query.chars().collect();
+        for (line, text) in self.text.iter().enumerate() {
+            let mut start = 0;
+            while start + needle.len() <= text.len() {
+                let hit = text[start..start + needle.len()]
+                    .iter()
+                    .zip(&needle)
+                    .all(|(a, b)| chars_match(*a, *b, ignore_case));
+                if hit {
+                    self.matches.push(Match {
+                        line,
+                        start,
+                        end: start + needle.len(),
+                    });
+                    start += needle.len();
+                } else {
+                    start += 1;
+                }
+            }
+        }
+
+        let top = self.top();
+        self.current = self
+            .matches
+            .iter()
+            .position(|m| m.line >= top)
+            .or((!self.matches.is_empty()).then_some(0));
+        self.reveal();
+    }
+
+    /// Move to the next match, or the previous one, wrapping around
+    pub fn next_match(&mut self, forward: bool) {
+        let count = self.matches.len();
+        let Some(current) = self.current else {
+            return;
+        };
+        self.current = Some(if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        });
+        self.reveal();
+    }
+
+    /// Scroll the highlighted match into the middle of the screen unless
+    /// it's already visible
+    fn reveal(&mut self) {
+        let Some(m) = self.current.map(|i| self.matches[i]) else {
+            return;
+        };
+        let top = self.top();
+        if m.line < top || m.line >= top + self.height {
+            self.top = m.line.saturating_sub(self.height / 2).min(self.max_top());
+        }
+    }
+
+    /// `3/10` for the highlighted match, when searching
+    pub fn match_status(&self) -> Option<String> {
+        if self.query.is_empty() {
+            return None;
+        }
+        Some(match self.current {
+            Some(current) => format!("{}/{}", current + 1, self.matches.len()),
+            None => "no matches".to_string(),
+        })
+    }
+
+    /// The lines on screen, with matches highlighted
+    pub fn visible_lines(&self) -> Vec<Line<'static>> {
+        let top = self.top();
+        let end = (top + self.height).min(self.lines.len());
+        (top..end)
+            .map(|index| {
+                let mut line = self.lines[index].clone();
+                let first = self.matches.partition_point(|m| m.line < index);
+                for (i, m) in self.matches[first..].iter().enumerate() {
+                    if m.line != index {
+                        break;
+                    }
+                    let style = if self.current == Some(first + i) {
+                        Style::default().fg(Color::Black).bg(Color::Yellow)
+                    } else {
+                        Style::default().fg(Color::Black).bg(Color::DarkGray)
+                    };
+                    line = highlight(line, m.start, m.end, style);
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+fn chars_match(a: char, b: char, ignore_case: bool) -> bool {
+    if ignore_case {
+        a.to_lowercase().eq(b.to_lowercase())
+    } else {
+        a == b
+    }
+}
+
+/// Patch `style` onto characters `start..end` of a line, splitting spans
+/// where the range starts and ends
+fn highlight(line: Line<'static>, start: usize, end: usize, style: Style) -> Line<'static> {
+    let mut spans = Vec::with_capacity(line.spans.len() + 2);
+    let mut offset = 0;
+    for span in line.spans {
+        let len = span.content.chars().count();
+        let (from, to) = (start.max(offset), end.min(offset + len));
+        if from >= to {
+            spans.push(span);
+        } else {
+            let chars: Vec<char> = span.content.chars().collect();
+            let piece =
+                |a: usize, b: usize| chars[a - offset..b - offset].iter().collect::<String>();
+            if from > offset {
+                spans.push(Span::styled(piece(offset, from), span.style));
+            }
+            spans.push(Span::styled(piece(from, to), span.style.patch(style)));
+            if to < offset + len {
+                spans.push(Span::styled(piece(to, offset + len), span.style));
+            }
+        }
+        offset += len;
+    }
+    Line { spans, ..line }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pager(height: usize) -> Pager {
+        let content: Vec<String> = (0..100).map(|i| format!("line {}", i)).collect();
+        let mut pager = Pager::new("t".to_string(), &content.join("\n"));
+        pager.set_height(height);
+        pager
+    }
+
+    fn plain(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut pager = pager(10);
+        assert_eq!(pager.line_count(), 100);
+        assert_eq!(pager.top(), 90);
+        assert_eq!(plain(&pager.visible_lines()[9]), "line 99");
+
+        pager.scroll(5);
+        assert_eq!(pager.top(), 90);
+        pager.page(-2);
+        assert_eq!(pager.top(), 70);
+        pager.scroll_to_top();
+        pager.scroll(-1);
+        assert_eq!(pager.top(), 0);
+        pager.scroll_to_bottom();
+        assert_eq!(pager.top(), 90);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut pager = pager(10);
+        pager.scroll_to_top();
+        pager.search("LINE 5");
+        assert_eq!(pager.match_status().as_deref(), Some("no matches"));
+
+        pager.search("line 5");
+        assert_eq!(pager.match_status().as_deref(), Some("1/11"));
+        assert_eq!(pager.top(), 0);
+
+        pager.next_match(true);
+        assert_eq!(pager.match_status().as_deref(), Some("2/11"));
+        assert_eq!(pager.top(), 45);
+
+        pager.next_match(false);
+        pager.next_match(false);
+        assert_eq!(pager.match_status().as_deref(), Some("11/11"));
+        assert_eq!(pager.top(), 54);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let line = Line::from(vec![
+            Span::raw("ab"),
+            Span::styled("cdef", Style::default().fg(Color::Red)),
+        ]);
+        let style = Style::default().bg(Color::Yellow);
+        let line = highlight(line, 1, 3, style);
+        let pieces: Vec<&str> = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(pieces, ["a", "b", "c", "def"]);
+        assert_eq!(line.spans[1].style.bg, Some(Color::Yellow));
+        assert_eq!(line.spans[2].style.fg, Some(Color::Red));
+        assert_eq!(line.spans[2].style.bg, Some(Color::Yellow));
+        assert_eq!(line.spans[3].style.bg, None);
+    }
+}
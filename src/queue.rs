@@ -0,0 +1,387 @@
+//! Prompts queued for agents
+//!
+//! Each session can have a list of prompts waiting to be sent. When the
+//! session's agent goes from working to idle, the next prompt is typed into
+//! its agent pane. The queues live in `queue.json` in the state directory so
+//! they survive restarts; the daemon sends the prompts while it runs, the
+//! TUI otherwise. Several TUIs may see the same agent finish, so a queue
+//! records whether its agent was seen working since the last prompt went
+//! out, and only one of them sends the next one.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::session::{ClaudeCodeStatus, Session};
+use crate::tmux::Tmux;
+
+/// Prompts waiting for one session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptQueue {
+    /// Keep the prompts but don't send them
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub prompts: Vec<String>,
+    /// The agent was seen working since the last prompt was sent, so its
+    /// next finish hasn't been answered yet
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub working: bool,
+}
+
+/// Every session's queue, keyed by [`key`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Queues {
+    sessions: BTreeMap<String, PromptQueue>,
+}
+
+/// A queued prompt that was sent (or failed to be)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dispatch {
+    pub session_name: String,
+    pub prompt: String,
+    pub error: Option<String>,
+}
+
+/// Key of a session's queue: its ID and creation time, like `$3@1700000000`.
+/// tmux reuses IDs after a server restart, and a queue must not carry over
+/// to an unrelated session that got the same ID.
+pub fn key(session: &Session) -> String {
+    format!("{}@{}", session.id, session.created)
+}
+
+/// Path of the queue file
+pub fn queue_path() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join("queue.json"))
+}
+
+/// Read the queues; a missing or unreadable file means no queues
+pub fn load() -> Queues {
+    queue_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Change the queues on disk and return them with `f`'s result. The file
+/// is read and written while holding an exclusive lock on `queue.lock`, so
+/// concurrent edits from the TUI and the daemon are applied one after the
+/// other instead of overwriting each other.
+pub fn update<T>(f: impl FnOnce(&mut Queues) -> T) -> Result<(Queues, T)> {
+    let Some(path) = queue_path() else {
+        let mut queues = load();
+        let result = f(&mut queues);
+        return Ok((queues, result));
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    // Released when the file is closed at the end of the function
+    let lock = lock_file(&path.with_extension("lock"))?;
+
+    let mut queues = load();
+    let result = f(&mut queues);
+    queues
+        .sessions
+        .retain(|_, queue| queue.paused || !queue.prompts.is_empty());
+
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(&queues)?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))?;
+    drop(lock);
+    Ok((queues, result))
+}
+
+/// Open and exclusively lock `path`, waiting for other holders
+fn lock_file(path: &std::path::Path) -> Result<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.lock()
+        .with_context(|| format!("Failed to lock {}", path.display()))?;
+    Ok(file)
+}
+
+impl Queues {
+    /// The queue under a [`key`], if there is one
+    pub fn get(&self, key: &str) -> Option<&PromptQueue> {
+        self.sessions.get(key)
+    }
+
+    /// Add a prompt at the end of a queue
+    pub fn push(&mut self, key: &str, prompt: String) {
+        self.sessions
+            .entry(key.to_string())
+            .or_default()
+            .prompts
+            .push(prompt);
+    }
+
+    /// Remove the prompt at `index`
+    pub fn remove(&mut self, key: &str, index: usize) -> Option<String> {
+        let queue = self.sessions.get_mut(key)?;
+        (index < queue.prompts.len()).then(|| queue.prompts.remove(index))
+    }
+
+    /// Move the prompt at `index` up or down by one and return its new index
+    pub fn move_prompt(&mut self, key: &str, index: usize, down: bool) -> usize {
+        let Some(queue) = self.sessions.get_mut(key) else {
+            return index;
+        };
+        let target = if down {
+            index + 1
+        } else {
+            index.wrapping_sub(1)
+        };
+        if index < queue.prompts.len() && target < queue.prompts.len() {
+            queue.prompts.swap(index, target);
+            target
+        } else {
+            index
+        }
+    }
+
+    /// Pause or resume a queue and return whether it is paused
+    pub fn toggle_paused(&mut self, key: &str) -> bool {
+        let queue = self.sessions.entry(key.to_string()).or_default();
+        queue.paused = !queue.paused;
+        queue.paused
+    }
+
+    /// Note that the agent of a queue's session is working
+    pub fn mark_working(&mut self, key: &str) {
+        if let Some(queue) = self.sessions.get_mut(key) {
+            queue.working = true;
+        }
+    }
+
+    /// Take the next prompt of a queue unless it is paused
+    pub fn pop_next(&mut self, key: &str) -> Option<String> {
+        let queue = self.sessions.get_mut(key)?;
+        if queue.paused || queue.prompts.is_empty() {
+            return None;
+        }
+        Some(queue.prompts.remove(0))
+    }
+
+    /// Drop the queues of sessions that no longer exist
+    fn prune(&mut self, sessions: &[Session]) {
+        let keys: Vec<String> = sessions.iter().map(key).collect();
+        self.sessions.retain(|k, _| keys.contains(k));
+    }
+}
+
+/// Agent status of every session, keyed by session ID
+pub fn session_statuses(sessions: &[Session]) -> HashMap<String, ClaudeCodeStatus> {
+    sessions
+        .iter()
+        .filter(|s| s.agent.is_some())
+        .map(|s| (s.id.clone(), s.claude_code_status))
+        .collect()
+}
+
+/// Sessions whose agent went from working to idle since `before` (see
+/// [`session_statuses`])
+pub fn finished<'a>(
+    before: &HashMap<String, ClaudeCodeStatus>,
+    sessions: &'a [Session],
+) -> Vec<&'a Session> {
+    sessions
+        .iter()
+        .filter(|s| {
+            before.get(&s.id) == Some(&ClaudeCodeStatus::Working)
+                && s.claude_code_status == ClaudeCodeStatus::Idle
+        })
+        .collect()
+}
+
+/// Take the next prompt for every session that finished working, unless
+/// another process already sent one for this finish, and mark the queues
+/// of sessions that are working now
+fn take_prompts<'a>(
+    queues: &mut Queues,
+    before: &HashMap<String, ClaudeCodeStatus>,
+    sessions: &'a [Session],
+) -> Vec<(&'a Session, String)> {
+    // An empty list is more likely a failed refresh than no sessions
+    if !sessions.is_empty() {
+        queues.prune(sessions);
+    }
+
+    let mut popped = Vec::new();
+    for session in finished(before, sessions) {
+        let key = key(session);
+        if !queues.get(&key).is_some_and(|queue| queue.working) {
+            continue;
+        }
+        if let Some(prompt) = queues.pop_next(&key) {
+            if let Some(queue) = queues.sessions.get_mut(&key) {
+                queue.working = false;
+            }
+            popped.push((session, prompt));
+        }
+    }
+    for session in sessions {
+        if session.claude_code_status == ClaudeCodeStatus::Working {
+            queues.mark_working(&key(session));
+        }
+    }
+    popped
+}
+
+/// Send the next queued prompt to every session that finished working
+pub fn dispatch(
+    before: &HashMap<String, ClaudeCodeStatus>,
+    sessions: &[Session],
+) -> Result<Vec<Dispatch>> {
+    let queues = load();
+    let unmarked = sessions.iter().any(|s| {
+        s.claude_code_status == ClaudeCodeStatus::Working
+            && queues.get(&key(s)).is_some_and(|queue| !queue.working)
+    });
+    if queues == Queues::default() || (!unmarked && finished(before, sessions).is_empty()) {
+        return Ok(Vec::new());
+    }
+
+    let (_, popped) = update(|queues| take_prompts(queues, before, sessions))?;
+
+    Ok(popped
+        .into_iter()
+        .map(|(session, prompt)| {
+            let target = session.claude_code_pane.as_deref().unwrap_or(&session.id);
+            let error = Tmux::send_text(target, &prompt, true, true)
+                .err()
+                .map(|e| format!("{:#}", e));
+            Dispatch {
+                session_name: session.name.clone(),
+                prompt,
+                error,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::fixtures::session;
+
+    fn queues() -> Queues {
+        let mut queues = Queues::default();
+        for prompt in ["one", "two", "three"] {
+            queues.push("$1", prompt.to_string());
+        }
+        queues
+    }
+
+    #[test]
+    fn test_pop_next() {
+        let mut queues = queues();
+        assert_eq!(queues.pop_next("$1").as_deref(), Some("one"));
+        assert_eq!(queues.pop_next("$2"), None);
+
+        assert!(queues.toggle_paused("$1"));
+        assert_eq!(queues.pop_next("$1"), None);
+        assert!(!queues.toggle_paused("$1"));
+        assert_eq!(queues.pop_next("$1").as_deref(), Some("two"));
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut queues = queues();
+        assert_eq!(queues.move_prompt("$1", 0, true), 1);
+        assert_eq!(queues.move_prompt("$1", 0, false), 0);
+        assert_eq!(queues.move_prompt("$1", 2, true), 2);
+        assert_eq!(queues.get("$1").unwrap().prompts, ["two", "one", "three"]);
+
+        assert_eq!(queues.remove("$1", 1).as_deref(), Some("one"));
+        assert_eq!(queues.remove("$1", 5), None);
+        assert_eq!(queues.get("$1").unwrap().prompts, ["two", "three"]);
+    }
+
+    #[test]
+    fn test_take_prompts_once_per_finish() {
+        let working = [session("$1", ClaudeCodeStatus::Working)];
+        let idle = [session("$1", ClaudeCodeStatus::Idle)];
+        let key = key(&working[0]);
+        let mut queues = Queues::default();
+        for prompt in ["one", "two", "three"] {
+            queues.push(&key, prompt.to_string());
+        }
+
+        // Seen working, then two processes see the same finish
+        let none = session_statuses(&idle);
+        assert!(take_prompts(&mut queues, &none, &working).is_empty());
+        let before = session_statuses(&working);
+        let first = take_prompts(&mut queues, &before, &idle);
+        let second = take_prompts(&mut queues, &before, &idle);
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].1, "one");
+        assert!(second.is_empty());
+
+        // The next finish sends the next prompt
+        assert!(take_prompts(&mut queues, &none, &working).is_empty());
+        let next = take_prompts(&mut queues, &before, &idle);
+        assert_eq!(next[0].1, "two");
+        assert_eq!(queues.get(&key).unwrap().prompts, ["three"]);
+    }
+
+    #[test]
+    fn test_prune_by_created() {
+        let old = session("$1", ClaudeCodeStatus::Idle);
+        let mut queues = Queues::default();
+        queues.push(&key(&old), "stale".to_string());
+        assert_eq!(key(&old), "$1@0");
+
+        // After a tmux restart, $1 is a different session
+        let new = Session {
+            created: 1700000000,
+            ..session("$1", ClaudeCodeStatus::Idle)
+        };
+        queues.prune(std::slice::from_ref(&new));
+        assert_eq!(queues.pop_next(&key(&new)), None);
+        assert_eq!(queues, Queues::default());
+    }
+
+    #[test]
+    fn test_json() {
+        let mut queues = queues();
+        queues.toggle_paused("$1");
+        let json = serde_json::to_string(&queues).unwrap();
+        assert_eq!(
+            json,
+            r#"{"$1":{"paused":true,"prompts":["one","two","three"]}}"#
+        );
+        assert_eq!(serde_json::from_str::<Queues>(&json).unwrap(), queues);
+    }
+
+    #[test]
+    fn test_finished() {
+        let before = session_statuses(&[
+            session("$1", ClaudeCodeStatus::Working),
+            session("$2", ClaudeCodeStatus::Working),
+            session("$3", ClaudeCodeStatus::Idle),
+        ]);
+        let after = [
+            session("$1", ClaudeCodeStatus::Idle),
+            session("$2", ClaudeCodeStatus::WaitingInput),
+            session("$3", ClaudeCodeStatus::Idle),
+            session("$4", ClaudeCodeStatus::Idle),
+        ];
+        let finished: Vec<&str> = finished(&before, &after)
+            .iter()
+            .map(|s| s.id.as_str())
+            .collect();
+        assert_eq!(finished, ["$1"]);
+    }
+}
@@ -1,13 +1,22 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-/// Status of a Claude Code instance in a pane
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+use serde::{Deserialize, Serialize};
+
+use crate::agents::Agent;
+
+/// Status of a Claude Code instance (or another coding agent) in a pane
+///
+/// Serializes as its [`label`](Self::label).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ClaudeCodeStatus {
     /// Waiting at prompt, ready for input
     Idle,
     /// Actively processing a request
     Working,
     /// Awaiting user confirmation/input (y/n prompt, etc.)
+    #[serde(rename = "input", alias = "waiting_input")]
     WaitingInput,
     /// Cannot determine status
     #[default]
@@ -34,13 +43,33 @@ impl ClaudeCodeStatus {
             ClaudeCodeStatus::Unknown => "unknown",
         }
     }
+
+    /// How much the status needs the user's attention; higher is more urgent
+    pub fn urgency(&self) -> u8 {
+        match self {
+            ClaudeCodeStatus::WaitingInput => 3,
+            ClaudeCodeStatus::Working => 2,
+            ClaudeCodeStatus::Idle => 1,
+            ClaudeCodeStatus::Unknown => 0,
+        }
+    }
+
+    /// Word used in combined summaries such as "1 waiting, 2 working"
+    fn summary_word(&self) -> &'static str {
+        match self {
+            ClaudeCodeStatus::WaitingInput => "waiting",
+            other => other.label(),
+        }
+    }
 }
 
 /// A tmux pane within a session
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pane {
     /// Pane ID (e.g., "%0")
     pub id: String,
+    /// Index of the window containing the pane
+    pub window_index: u32,
     /// Current command running in the pane
     pub current_command: String,
     /// Current working directory
@@ -49,45 +78,184 @@ pub struct Pane {
     pub pid: u32,
     /// Pane title (set by the running program)
     pub title: String,
+    /// Coding agent running in the pane, if any
+    pub agent: Option<Agent>,
+    /// Status of the agent; `Unknown` for panes without one
+    pub status: ClaudeCodeStatus,
+    /// What the agent is asking while it waits for input
+    #[serde(default)]
+    pub question: Option<Question>,
+    /// Whether this is the active pane of its window
+    #[serde(default)]
+    pub active: bool,
+}
+
+impl Pane {
+    /// Returns a shortened version of the pane's directory for display
+    pub fn display_path(&self) -> String {
+        shorten_home(&self.current_path)
+    }
+}
+
+/// Replace the home directory at the start of `path` with `~`
+fn shorten_home(path: &Path) -> String {
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(stripped) = path.strip_prefix(&home) {
+            if stripped.as_os_str().is_empty() {
+                return "~".to_string();
+            }
+            return format!("~/{}", stripped.display());
+        }
+    }
+    path.display().to_string()
+}
+
+/// A tmux window within a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Window {
+    pub index: u32,
+    pub name: String,
+    /// Whether this is the session's current window
+    pub active: bool,
+}
+
+/// One row of a session's window/pane tree
+#[derive(Debug, Clone, Copy)]
+pub enum TreeNode<'a> {
+    Window {
+        index: u32,
+        name: &'a str,
+        active: bool,
+    },
+    Pane(&'a Pane),
+}
+
+/// What a waiting agent is asking, as read from its pane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Question {
+    /// Kind of request, e.g. "Bash command", "Edit file" or "Plan"
+    pub kind: Option<String>,
+    /// Command or file being touched, or the first line of the plan
+    pub subject: Option<String>,
+    /// Full prompt text, without the menu options
+    pub text: String,
+}
+
+impl Question {
+    /// One-line description, e.g. "Bash command: cargo test"
+    pub fn summary(&self) -> String {
+        match (&self.kind, &self.subject) {
+            (Some(kind), Some(subject)) => format!("{}: {}", kind, subject),
+            (Some(kind), None) => kind.clone(),
+            (None, Some(subject)) => subject.clone(),
+            (None, None) => self.text.lines().last().unwrap_or("").to_string(),
+        }
+    }
 }
 
 /// A tmux session that may contain a Claude Code instance
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct Session {
+    /// Session ID (e.g., "$1"), stable across renames
+    pub id: String,
     /// Session name
     pub name: String,
     /// Unix timestamp when session was created
     pub created: i64,
     /// Whether a client is attached to this session
     pub attached: bool,
-    /// Working directory (from the Claude Code pane, or first pane)
+    /// Working directory (from the agent pane, or first pane)
     pub working_directory: PathBuf,
     /// Number of windows in this session
     pub window_count: usize,
+    /// Windows in index order
+    #[serde(default)]
+    pub windows: Vec<Window>,
     /// All panes in this session
     pub panes: Vec<Pane>,
-    /// Pane ID containing Claude Code, if any
+    /// Coding agent found in this session, if any
+    pub agent: Option<Agent>,
+    /// Agent pane needing the most attention, if any
     pub claude_code_pane: Option<String>,
-    /// Status of Claude Code in this session
+    /// Most urgent status among the session's agent panes
     pub claude_code_status: ClaudeCodeStatus,
-    /// Pane title of the Claude Code pane (or first pane)
+    /// Pane title of the agent pane (or first pane)
     pub pane_title: String,
+    /// Whether notifications are muted (the `@tsm-muted` session option)
+    pub muted: bool,
+    /// Unix timestamp of the last change of `claude_code_status`, when known
+    #[serde(default)]
+    pub status_since: Option<u64>,
 }
 
 impl Session {
-    /// Returns a shortened version of the working directory for display
-    pub fn display_path(&self) -> String {
-        let path = &self.working_directory;
+    /// Panes running a coding agent
+    pub fn agent_panes(&self) -> impl Iterator<Item = &Pane> {
+        self.panes.iter().filter(|p| p.agent.is_some())
+    }
 
-        // Try to replace home directory with ~
-        if let Some(home) = dirs::home_dir() {
-            if let Ok(stripped) = path.strip_prefix(&home) {
-                return format!("~/{}", stripped.display());
+    /// Recompute the session-wide agent fields from the panes. The most
+    /// urgent agent pane (the first one on ties) represents the session.
+    pub fn update_agent_summary(&mut self) {
+        let mut primary: Option<&Pane> = None;
+        for pane in self.agent_panes() {
+            if primary.is_none_or(|p| pane.status.urgency() > p.status.urgency()) {
+                primary = Some(pane);
             }
         }
 
-        path.display().to_string()
+        let (agent, pane_id, status) = match primary {
+            Some(pane) => (pane.agent, Some(pane.id.clone()), pane.status),
+            None => (None, None, ClaudeCodeStatus::Unknown),
+        };
+        self.agent = agent;
+        self.claude_code_pane = pane_id;
+        self.claude_code_status = status;
+    }
+
+    /// Set the status and pending question of one agent pane. Returns
+    /// false if the session has no such pane.
+    pub fn set_pane_status(
+        &mut self,
+        pane_id: &str,
+        status: ClaudeCodeStatus,
+        question: Option<Question>,
+    ) -> bool {
+        let Some(pane) = self.panes.iter_mut().find(|p| p.id == pane_id) else {
+            return false;
+        };
+        pane.status = status;
+        pane.question = question;
+        self.update_agent_summary();
+        true
+    }
+
+    /// Status of all agent panes, most urgent first, e.g. "1 waiting, 2 working".
+    /// Sessions with a single agent pane just use the status label.
+    pub fn status_summary(&self) -> String {
+        if self.agent_panes().count() <= 1 {
+            return self.claude_code_status.label().to_string();
+        }
+
+        [
+            ClaudeCodeStatus::WaitingInput,
+            ClaudeCodeStatus::Working,
+            ClaudeCodeStatus::Idle,
+            ClaudeCodeStatus::Unknown,
+        ]
+        .iter()
+        .filter_map(|status| {
+            let count = self.agent_panes().filter(|p| p.status == *status).count();
+            (count > 0).then(|| format!("{} {}", count, status.summary_word()))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    /// Returns a shortened version of the working directory for display
+    pub fn display_path(&self) -> String {
+        shorten_home(&self.working_directory)
     }
 
     /// Returns a human-readable duration since session creation
@@ -101,16 +269,350 @@ impl Session {
 
         let elapsed_secs = (now - self.created).max(0) as u64;
 
-        let days = elapsed_secs / 86400;
-        let hours = (elapsed_secs % 86400) / 3600;
-        let minutes = (elapsed_secs % 3600) / 60;
+        format_elapsed(elapsed_secs.max(60))
+    }
+
+    /// What the most urgent agent pane is asking, if it is waiting
+    pub fn pending_question(&self) -> Option<&Question> {
+        let pane_id = self.claude_code_pane.as_deref()?;
+        self.panes
+            .iter()
+            .find(|p| p.id == pane_id)
+            .and_then(|p| p.question.as_ref())
+    }
+
+    /// Pane shown in previews: the agent pane, or else the first pane
+    pub fn preview_pane(&self) -> Option<&str> {
+        self.claude_code_pane
+            .as_deref()
+            .or_else(|| self.panes.first().map(|p| p.id.as_str()))
+    }
+
+    /// Windows followed by their panes, in index order. Snapshots from
+    /// before windows were recorded get nameless windows from the panes.
+    pub fn tree(&self) -> Vec<TreeNode<'_>> {
+        let mut windows: Vec<TreeNode> = self
+            .windows
+            .iter()
+            .map(|w| TreeNode::Window {
+                index: w.index,
+                name: &w.name,
+                active: w.active,
+            })
+            .collect();
+        if windows.is_empty() {
+            let mut indices: Vec<u32> = self.panes.iter().map(|p| p.window_index).collect();
+            indices.dedup();
+            windows = indices
+                .into_iter()
+                .map(|index| TreeNode::Window {
+                    index,
+                    name: "",
+                    active: false,
+                })
+                .collect();
+        }
 
-        if days > 0 {
-            format!("{}d {}h", days, hours)
-        } else if hours > 0 {
-            format!("{}h {}m", hours, minutes)
-        } else {
-            format!("{}m", minutes.max(1))
+        let mut nodes = Vec::new();
+        for window in windows {
+            let TreeNode::Window { index, .. } = window else {
+                continue;
+            };
+            nodes.push(window);
+            nodes.extend(
+                self.panes
+                    .iter()
+                    .filter(|p| p.window_index == index)
+                    .map(TreeNode::Pane),
+            );
         }
+        nodes
+    }
+
+    /// tmux target of a tree node, e.g. `$1:2` or `$1:2.%5`
+    pub fn tree_target(&self, node: &TreeNode) -> String {
+        match node {
+            TreeNode::Window { index, .. } => format!("{}:{}", self.id, index),
+            TreeNode::Pane(pane) => format!("{}:{}.{}", self.id, pane.window_index, pane.id),
+        }
+    }
+
+    /// Seconds the session has been in its current status, when known
+    pub fn time_in_status(&self, now: u64) -> Option<u64> {
+        self.status_since.map(|since| now.saturating_sub(since))
+    }
+}
+
+/// Number of agent panes in each status, across sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusCounts {
+    pub working: usize,
+    pub waiting: usize,
+    pub idle: usize,
+    pub unknown: usize,
+    /// Number of sessions, with or without agents
+    pub sessions: usize,
+}
+
+impl StatusCounts {
+    pub fn of(sessions: &[Session]) -> Self {
+        let mut counts = Self {
+            sessions: sessions.len(),
+            ..Self::default()
+        };
+        for pane in sessions.iter().flat_map(|s| s.agent_panes()) {
+            match pane.status {
+                ClaudeCodeStatus::Working => counts.working += 1,
+                ClaudeCodeStatus::WaitingInput => counts.waiting += 1,
+                ClaudeCodeStatus::Idle => counts.idle += 1,
+                ClaudeCodeStatus::Unknown => counts.unknown += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format a number of seconds compactly: "45s", "12m", "3h 5m", "2d 4h"
+pub fn format_elapsed(secs: u64) -> String {
+    let days = secs / 86400;
+    let hours = (secs % 86400) / 3600;
+    let minutes = (secs % 3600) / 60;
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Remembers when each session's status last changed and stamps
+/// [`Session::status_since`] on refreshed session lists
+#[derive(Debug, Default)]
+pub struct StatusClock {
+    seen: HashMap<String, (ClaudeCodeStatus, u64)>,
+}
+
+impl StatusClock {
+    /// Record the current status of every session at time `now` and set
+    /// its `status_since`. Sessions seen for the first time start at `now`.
+    pub fn stamp(&mut self, sessions: &mut [Session], now: u64) {
+        self.seen
+            .retain(|id, _| sessions.iter().any(|s| &s.id == id));
+
+        for session in sessions.iter_mut() {
+            if session.agent.is_none() {
+                self.seen.remove(&session.id);
+                session.status_since = None;
+                continue;
+            }
+
+            let entry = self
+                .seen
+                .entry(session.id.clone())
+                .or_insert((session.claude_code_status, now));
+            if entry.0 != session.claude_code_status {
+                *entry = (session.claude_code_status, now);
+            }
+            session.status_since = Some(entry.1);
+        }
+    }
+}
+
+/// Session fixtures shared by the tests of every module
+#[cfg(test)]
+pub mod fixtures {
+    use super::*;
+
+    /// Pane in `/tmp`, running Claude Code when `status` is given
+    pub fn pane(id: &str, status: Option<ClaudeCodeStatus>) -> Pane {
+        Pane {
+            id: id.to_string(),
+            window_index: 0,
+            current_command: "claude".to_string(),
+            current_path: PathBuf::from("/tmp"),
+            pid: 1,
+            title: String::new(),
+            agent: status.map(|_| Agent::Claude),
+            status: status.unwrap_or_default(),
+            question: None,
+            active: true,
+        }
+    }
+
+    /// Session "work" in `/tmp` holding `panes`
+    pub fn session_with_panes(id: &str, panes: Vec<Pane>) -> Session {
+        let mut session = Session {
+            id: id.to_string(),
+            name: "work".to_string(),
+            created: 0,
+            attached: false,
+            working_directory: PathBuf::from("/tmp"),
+            window_count: 1,
+            windows: vec![],
+            panes,
+            agent: None,
+            claude_code_pane: None,
+            claude_code_status: ClaudeCodeStatus::Unknown,
+            pane_title: String::new(),
+            muted: false,
+            status_since: None,
+        };
+        session.update_agent_summary();
+        session
+    }
+
+    /// Session `$N` with a single Claude Code pane `%N` in `status`
+    pub fn session(id: &str, status: ClaudeCodeStatus) -> Session {
+        session_with_panes(id, vec![pane(&format!("%{}", &id[1..]), Some(status))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{pane, session_with_panes};
+    use super::*;
+
+    fn session(panes: Vec<Pane>) -> Session {
+        session_with_panes("$1", panes)
+    }
+
+    #[test]
+    fn test_tree() {
+        let mut panes = vec![
+            pane("%0", None),
+            pane("%1", Some(ClaudeCodeStatus::Idle)),
+            pane("%2", None),
+        ];
+        panes[1].window_index = 2;
+        panes[2].window_index = 2;
+        let mut session = session(panes);
+
+        // Without window records, windows come from the panes
+        let tree = session.tree();
+        assert_eq!(tree.len(), 5);
+        assert!(matches!(
+            tree[2],
+            TreeNode::Window {
+                index: 2,
+                name: "",
+                ..
+            }
+        ));
+
+        session.windows = vec![
+            Window {
+                index: 0,
+                name: "editor".to_string(),
+                active: false,
+            },
+            Window {
+                index: 2,
+                name: "agent".to_string(),
+                active: true,
+            },
+        ];
+        let tree = session.tree();
+        let rows: Vec<String> = tree.iter().map(|n| session.tree_target(n)).collect();
+        assert_eq!(rows, ["$1:0", "$1:0.%0", "$1:2", "$1:2.%1", "$1:2.%2"]);
+        assert!(matches!(
+            tree[2],
+            TreeNode::Window {
+                name: "agent",
+                active: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_most_urgent_pane_represents_session() {
+        let session = session(vec![
+            pane("%0", None),
+            pane("%1", Some(ClaudeCodeStatus::Working)),
+            pane("%2", Some(ClaudeCodeStatus::WaitingInput)),
+            pane("%3", Some(ClaudeCodeStatus::Working)),
+        ]);
+        assert_eq!(session.claude_code_pane.as_deref(), Some("%2"));
+        assert_eq!(session.claude_code_status, ClaudeCodeStatus::WaitingInput);
+        assert_eq!(session.status_summary(), "1 waiting, 2 working");
+    }
+
+    #[test]
+    fn test_set_pane_status() {
+        let mut session = session(vec![
+            pane("%1", Some(ClaudeCodeStatus::Idle)),
+            pane("%2", Some(ClaudeCodeStatus::Idle)),
+        ]);
+        assert_eq!(session.claude_code_pane.as_deref(), Some("%1"));
+
+        assert!(session.set_pane_status("%2", ClaudeCodeStatus::Working, None));
+        assert_eq!(session.claude_code_pane.as_deref(), Some("%2"));
+        assert_eq!(session.status_summary(), "1 working, 1 idle");
+        assert!(!session.set_pane_status("%9", ClaudeCodeStatus::Working, None));
+    }
+
+    #[test]
+    fn test_status_clock() {
+        let mut clock = StatusClock::default();
+        let mut sessions = vec![session(vec![pane("%1", Some(ClaudeCodeStatus::Working))])];
+
+        clock.stamp(&mut sessions, 100);
+        assert_eq!(sessions[0].status_since, Some(100));
+
+        // Same status keeps the original time, even on a fresh session list
+        let mut sessions = vec![session(vec![pane("%1", Some(ClaudeCodeStatus::Working))])];
+        clock.stamp(&mut sessions, 160);
+        assert_eq!(sessions[0].status_since, Some(100));
+        assert_eq!(sessions[0].time_in_status(160), Some(60));
+
+        sessions[0].set_pane_status("%1", ClaudeCodeStatus::WaitingInput, None);
+        clock.stamp(&mut sessions, 200);
+        assert_eq!(sessions[0].status_since, Some(200));
+    }
+
+    #[test]
+    fn test_status_counts() {
+        let sessions = [
+            session(vec![
+                pane("%0", None),
+                pane("%1", Some(ClaudeCodeStatus::Working)),
+                pane("%2", Some(ClaudeCodeStatus::WaitingInput)),
+            ]),
+            session(vec![pane("%3", Some(ClaudeCodeStatus::Working))]),
+            session(vec![pane("%4", None)]),
+        ];
+        let counts = StatusCounts::of(&sessions);
+        assert_eq!(counts.working, 2);
+        assert_eq!(counts.waiting, 1);
+        assert_eq!(counts.idle, 0);
+        assert_eq!(counts.sessions, 3);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(45), "45s");
+        assert_eq!(format_elapsed(12 * 60 + 5), "12m");
+        assert_eq!(format_elapsed(3 * 3600 + 5 * 60), "3h 5m");
+        assert_eq!(format_elapsed(2 * 86400 + 4 * 3600), "2d 4h");
+    }
+
+    #[test]
+    fn test_single_pane_summary_is_label() {
+        let session = session(vec![pane("%1", Some(ClaudeCodeStatus::WaitingInput))]);
+        assert_eq!(session.status_summary(), "input");
     }
 }
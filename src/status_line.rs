@@ -0,0 +1,161 @@
+//! Compact agent status summary for tmux's status line
+//!
+//! `tsm status-line` prints a template like `⚙{working} ⏳{waiting}` filled
+//! in with agent pane counts, meant for `#(tsm status-line)` in
+//! `status-right`. It runs every `status-interval`, so it reads the daemon's
+//! snapshot when one is available and otherwise caches its own counts for a
+//! few seconds.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, Config};
+use crate::daemon;
+use crate::discovery::Discovery;
+use crate::session::{unix_now, StatusCounts};
+
+/// Template used when neither the command line nor the config sets one
+pub const DEFAULT_TEMPLATE: &str =
+    "#[fg=yellow]⚙{working}#[default] #[fg=red]⏳{waiting}#[default]";
+
+/// Counts computed without a daemon are reused for this long
+const CACHE_TTL: Duration = Duration::from_secs(3);
+
+/// Counts written to the cache file
+#[derive(Debug, Serialize, Deserialize)]
+struct Cache {
+    updated_at: u64,
+    counts: StatusCounts,
+}
+
+/// Print the status line
+pub fn run(template: Option<String>) -> Result<()> {
+    let config = Config::load()?;
+    let template = template.unwrap_or_else(|| config.status_line.template.clone());
+    let counts = current_counts(&config)?;
+    println!(
+        "{}",
+        render(&template, &counts, config.status_line.hide_zero)
+    );
+    Ok(())
+}
+
+/// Counts from the daemon, the cache, or a fresh discovery (in that order)
+fn current_counts(config: &Config) -> Result<StatusCounts> {
+    if let Some(snapshot) = daemon::read_snapshot() {
+        return Ok(StatusCounts::of(&snapshot.sessions));
+    }
+    if let Some(counts) = read_cache() {
+        return Ok(counts);
+    }
+
+    let counts = StatusCounts::of(&Discovery::new(config)?.list_sessions()?);
+    // A missing cache only makes the next run slower
+    let _ = write_cache(&counts);
+    Ok(counts)
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config::state_dir().map(|dir| dir.join("status-line.json"))
+}
+
+fn read_cache() -> Option<StatusCounts> {
+    let text = std::fs::read_to_string(cache_path()?).ok()?;
+    let cache: Cache = serde_json::from_str(&text).ok()?;
+    (unix_now().saturating_sub(cache.updated_at) < CACHE_TTL.as_secs()).then_some(cache.counts)
+}
+
+fn write_cache(counts: &StatusCounts) -> Result<()> {
+    let Some(path) = cache_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let cache = Cache {
+        updated_at: unix_now(),
+        counts: *counts,
+    };
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string(&cache)?)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Fill in `{working}`, `{waiting}`, `{idle}`, `{unknown}`, `{agents}` and
+/// `{sessions}`. With `hide_zero`, space-separated parts whose counts are
+/// all zero are left out.
+pub fn render(template: &str, counts: &StatusCounts, hide_zero: bool) -> String {
+    let values = [
+        ("working", counts.working),
+        ("waiting", counts.waiting),
+        ("idle", counts.idle),
+        ("unknown", counts.unknown),
+        (
+            "agents",
+            counts.working + counts.waiting + counts.idle + counts.unknown,
+        ),
+        ("sessions", counts.sessions),
+    ];
+
+    let mut parts = Vec::new();
+    for part in template.split(' ') {
+        let mut text = part.to_string();
+        let mut used = 0;
+        let mut nonzero = false;
+        for (name, value) in values {
+            let placeholder = format!("{{{}}}", name);
+            if text.contains(&placeholder) {
+                used += 1;
+                nonzero |= value > 0;
+                text = text.replace(&placeholder, &value.to_string());
+            }
+        }
+        if hide_zero && used > 0 && !nonzero {
+            continue;
+        }
+        parts.push(text);
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(working: usize, waiting: usize) -> StatusCounts {
+        StatusCounts {
+            working,
+            waiting,
+            sessions: 4,
+            ..StatusCounts::default()
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("⚙{working} ⏳{waiting}", &counts(2, 1), false),
+            "⚙2 ⏳1"
+        );
+        assert_eq!(
+            render("{agents}/{sessions} {idle}", &counts(2, 1), false),
+            "3/4 0"
+        );
+        assert_eq!(render("{nope}", &counts(0, 0), false), "{nope}");
+    }
+
+    #[test]
+    fn test_render_hides_zero_parts() {
+        assert_eq!(
+            render(DEFAULT_TEMPLATE, &counts(2, 0), true),
+            "#[fg=yellow]⚙2#[default]"
+        );
+        assert_eq!(render(DEFAULT_TEMPLATE, &counts(0, 0), true), "");
+        assert_eq!(render("tsm ⏳{waiting}", &counts(0, 0), true), "tsm");
+        assert_eq!(render("⏳{waiting}", &counts(0, 0), false), "⏳0");
+    }
+}
@@ -1,192 +1,26 @@
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use anyhow::{Context, Result};
 
-use crate::detection::detect_status;
-use crate::session::{Pane, Session};
+use crate::config::Config;
+use crate::daemon;
+use crate::discovery::Discovery;
+use crate::session::Session;
 
 /// Wrapper for tmux command execution
 pub struct Tmux;
 
 impl Tmux {
-    /// List all tmux sessions with their metadata
+    /// List all tmux sessions with their metadata, from the daemon's
+    /// snapshot when a daemon is running.
+    /// Long-running callers should keep a [`Discovery`] to reuse its caches.
     pub fn list_sessions() -> Result<Vec<Session>> {
-        let output = Command::new("tmux")
-            .args([
-                "list-sessions",
-                "-F",
-                "#{session_name}\t#{session_created}\t#{session_attached}\t#{session_windows}",
-            ])
-            .output()
-            .context("Failed to execute tmux list-sessions")?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // No sessions is not an error for us
-            if stderr.contains("no server running") || stderr.contains("no sessions") {
-                return Ok(Vec::new());
-            }
-            anyhow::bail!("tmux list-sessions failed: {}", stderr);
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut sessions = Vec::new();
-
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 4 {
-                let name = parts[0].to_string();
-                let created = parts[1].parse().unwrap_or(0);
-                let attached = parts[2] == "1";
-                let window_count = parts[3].parse().unwrap_or(1);
-
-                // Get panes for this session
-                let panes = Self::list_panes(&name).unwrap_or_default();
-
-                // Find Claude Code pane and detect status
-                let (claude_code_pane, claude_code_status, working_directory, pane_title) =
-                    Self::find_claude_code_pane(&name, &panes);
-
-                // Use the Claude Code pane's path, or fall back to first pane's path
-                let working_directory = working_directory.unwrap_or_else(|| {
-                    panes
-                        .first()
-                        .map(|p| p.current_path.clone())
-                        .unwrap_or_default()
-                });
-
-                sessions.push(Session {
-                    name,
-                    created,
-                    attached,
-                    working_directory,
-                    window_count,
-                    panes,
-                    claude_code_pane,
-                    claude_code_status,
-                    pane_title,
-                });
-            }
+        if let Some(snapshot) = daemon::read_snapshot() {
+            return Ok(snapshot.sessions);
         }
-
-        // Sort by attached status first, then by name
-        sessions.sort_by(|a, b| {
-            b.attached
-                .cmp(&a.attached)
-                .then_with(|| a.name.cmp(&b.name))
-        });
-
-        Ok(sessions)
-    }
-
-    /// List all panes in a session
-    fn list_panes(session: &str) -> Result<Vec<Pane>> {
-        let output = Command::new("tmux")
-            .args([
-                "list-panes",
-                "-t",
-                session,
-                "-F",
-                "#{pane_id}\t#{pane_current_command}\t#{pane_current_path}\t#{pane_pid}\t#{pane_title}",
-            ])
-            .output()
-            .context("Failed to execute tmux list-panes")?;
-
-        if !output.status.success() {
-            return Ok(Vec::new());
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut panes = Vec::new();
-
-        for line in stdout.lines() {
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 5 {
-                panes.push(Pane {
-                    id: parts[0].to_string(),
-                    current_command: parts[1].to_string(),
-                    current_path: PathBuf::from(parts[2]),
-                    pid: parts[3].parse().unwrap_or(0),
-                    title: parts[4].to_string(),
-                });
-            }
-        }
-
-        Ok(panes)
-    }
-
-    /// Check if a process or its parent is claude
-    fn is_claude_process(pane: &Pane) -> bool {
-        // Check pane_current_command directly
-        let cmd = &pane.current_command;
-        if cmd == "claude" || cmd.contains("claude") {
-            return true;
-        }
-
-        // Check actual process name via pid (pane_current_command can show version number)
-        if pane.pid > 0 {
-            if let Ok(output) = Command::new("ps")
-                .args(["-o", "command=", "-p", &pane.pid.to_string()])
-                .output()
-            {
-                let ps_cmd = String::from_utf8_lossy(&output.stdout);
-                if ps_cmd.trim() == "claude" || ps_cmd.contains("claude") {
-                    return true;
-                }
-            }
-        }
-
-        false
-    }
-
-    /// Find the pane running Claude Code and detect its status
-    /// Returns (pane_id, status, working_directory, pane_title)
-    fn find_claude_code_pane(
-        session_name: &str,
-        panes: &[Pane],
-    ) -> (
-        Option<String>,
-        crate::session::ClaudeCodeStatus,
-        Option<PathBuf>,
-        String,
-    ) {
-        use crate::session::ClaudeCodeStatus;
-
-        // First: check each pane's process
-        for pane in panes {
-            if Self::is_claude_process(pane) {
-                let status = Self::capture_pane(&pane.id, 4, true)
-                    .map(|content| detect_status(&content))
-                    .unwrap_or(ClaudeCodeStatus::Unknown);
-
-                return (
-                    Some(pane.id.clone()),
-                    status,
-                    Some(pane.current_path.clone()),
-                    pane.title.clone(),
-                );
-            }
-        }
-
-        // Fallback: session name starts with "claude" (e.g. claude_284B7F7A)
-        if session_name.starts_with("claude") {
-            if let Some(pane) = panes.first() {
-                let status = Self::capture_pane(&pane.id, 4, true)
-                    .map(|content| detect_status(&content))
-                    .unwrap_or(ClaudeCodeStatus::Unknown);
-
-                return (
-                    Some(pane.id.clone()),
-                    status,
-                    Some(pane.current_path.clone()),
-                    pane.title.clone(),
-                );
-            }
-        }
-
-        let title = panes.first().map(|p| p.title.clone()).unwrap_or_default();
-        (None, ClaudeCodeStatus::Unknown, None, title)
+        Discovery::new(&Config::load()?)?.list_sessions()
     }
 
     /// Capture the last N lines of a pane's content
@@ -233,6 +67,32 @@ impl Tmux {
         }
     }
 
+    /// Capture a pane's whole history, scrollback included, with its colors
+    pub fn capture_history(pane_id: &str) -> Result<String> {
+        let output = Command::new("tmux")
+            .args(["capture-pane", "-t", pane_id, "-p", "-J", "-e", "-S", "-"])
+            .output()
+            .context("Failed to capture pane")?;
+
+        if !output.status.success() {
+            anyhow::bail!("Failed to capture pane {}", pane_id);
+        }
+
+        let content = String::from_utf8_lossy(&output.stdout);
+        Ok(content.trim_end().to_string())
+    }
+
+    /// Target for a session given by name or `$id` on the command line.
+    /// Names are matched exactly: a bare name would also match any
+    /// session it is a prefix of.
+    pub fn session_target(key: &str) -> String {
+        if key.starts_with('$') {
+            key.to_string()
+        } else {
+            format!("={}", key)
+        }
+    }
+
     /// Switch to the specified session.
     /// Uses switch-client inside tmux, attach-session outside.
     pub fn switch_to_session(session: &str) -> Result<()> {
@@ -259,6 +119,39 @@ impl Tmux {
         Ok(())
     }
 
+    /// Switch a client to the specified session. Without `client`, the
+    /// most recently active client that isn't in control mode is switched,
+    /// never a control client like the one tsm itself follows changes with.
+    pub fn switch_client(session: &str, client: Option<&str>) -> Result<()> {
+        let client = match client {
+            Some(client) => client.to_string(),
+            None => Self::user_client()?.context("No tmux client to switch")?,
+        };
+        let status = Command::new("tmux")
+            .args(["switch-client", "-c", &client, "-t", session])
+            .status()
+            .context("Failed to switch session")?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to switch to session {}", session);
+        }
+
+        Ok(())
+    }
+
+    /// Name of the most recently active client that isn't in control mode
+    fn user_client() -> Result<Option<String>> {
+        let output = Command::new("tmux")
+            .args([
+                "list-clients",
+                "-F",
+                "#{client_activity}\t#{client_control_mode}\t#{client_name}",
+            ])
+            .output()
+            .context("Failed to list tmux clients")?;
+        Ok(pick_user_client(&String::from_utf8_lossy(&output.stdout)))
+    }
+
     /// Check if we're running inside a tmux session
     fn is_inside_tmux() -> bool {
         std::env::var("TMUX").is_ok()
@@ -301,6 +194,20 @@ impl Tmux {
         Ok(())
     }
 
+    /// Detach every client attached to a session
+    pub fn detach_session(session: &str) -> Result<()> {
+        let status = Command::new("tmux")
+            .args(["detach-client", "-s", session])
+            .status()
+            .context("Failed to detach clients")?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to detach clients from session {}", session);
+        }
+
+        Ok(())
+    }
+
     /// Rename a tmux session
     pub fn rename_session(old_name: &str, new_name: &str) -> Result<()> {
         let status = Command::new("tmux")
@@ -315,6 +222,204 @@ impl Tmux {
         Ok(())
     }
 
+    /// Create a window at the end of a session without switching to it
+    pub fn new_window(
+        session: &str,
+        name: Option<&str>,
+        path: &std::path::Path,
+        start_claude: bool,
+    ) -> Result<()> {
+        let target = format!("{}:", session);
+        let path_str = path.to_string_lossy();
+        let mut command = Command::new("tmux");
+        command.args([
+            "new-window",
+            "-d",
+            "-P",
+            "-F",
+            "#{pane_id}",
+            "-t",
+            &target,
+            "-c",
+            &path_str,
+        ]);
+        if let Some(name) = name {
+            command.args(["-n", name]);
+        }
+        let output = command.output().context("Failed to create new window")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to create window in {}: {}",
+                session,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        if start_claude {
+            let pane_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let _ = Command::new("tmux")
+                .args(["send-keys", "-t", &pane_id, "claude", "Enter"])
+                .status();
+        }
+
+        Ok(())
+    }
+
+    /// Kill a window, e.g. `$1:2`
+    pub fn kill_window(target: &str) -> Result<()> {
+        Self::run(&["kill-window", "-t", target], "kill window")
+    }
+
+    /// Kill a pane
+    pub fn kill_pane(pane_id: &str) -> Result<()> {
+        Self::run(&["kill-pane", "-t", pane_id], "kill pane")
+    }
+
+    /// Rename a window
+    pub fn rename_window(target: &str, new_name: &str) -> Result<()> {
+        Self::run(&["rename-window", "-t", target, new_name], "rename window")
+    }
+
+    /// Move a window to the end of another session
+    pub fn move_window(source: &str, session: &str) -> Result<()> {
+        let target = format!("{}:", session);
+        Self::run(
+            &["move-window", "-d", "-s", source, "-t", &target],
+            "move window",
+        )
+    }
+
+    /// Move a pane out of its window into a new window of a session
+    pub fn break_pane(pane_id: &str, session: &str) -> Result<()> {
+        let target = format!("{}:", session);
+        Self::run(
+            &["break-pane", "-d", "-s", pane_id, "-t", &target],
+            "break pane",
+        )
+    }
+
+    /// Move a pane into the current window of a session, split below its
+    /// active pane
+    pub fn join_pane(pane_id: &str, session: &str) -> Result<()> {
+        let target = format!("{}:", session);
+        Self::run(
+            &["join-pane", "-d", "-s", pane_id, "-t", &target],
+            "join pane",
+        )
+    }
+
+    /// Run a tmux command, failing with tmux's message when it does
+    fn run(args: &[&str], what: &str) -> Result<()> {
+        let output = Command::new("tmux")
+            .args(args)
+            .output()
+            .with_context(|| format!("Failed to {}", what))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to {}: {}",
+                what,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Send keys to a pane or session. With `literal`, the keys are sent
+    /// as text instead of being looked up as key names like `Enter`.
+    pub fn send_keys(target: &str, keys: &[String], literal: bool) -> Result<()> {
+        let status = Command::new("tmux")
+            .args(send_keys_args(target, keys, literal))
+            .status()
+            .context("Failed to send keys")?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to send keys to {}", target);
+        }
+
+        Ok(())
+    }
+
+    /// Type text into a pane: pasted when it spans several lines, otherwise
+    /// sent as keys (see [`Tmux::send_keys`]), then optionally Enter
+    pub fn send_text(target: &str, text: &str, enter: bool, literal: bool) -> Result<()> {
+        if text.contains('\n') {
+            Self::paste_text(target, text)?;
+        } else if literal {
+            if !text.is_empty() {
+                Self::send_keys(target, &[text.to_string()], true)?;
+            }
+        } else {
+            let keys = text_keys(text);
+            if !keys.is_empty() {
+                Self::send_keys(target, &keys, false)?;
+            }
+        }
+
+        if enter {
+            Self::send_keys(target, &["Enter".to_string()], false)?;
+        }
+        Ok(())
+    }
+
+    /// Paste text into a pane through a tmux buffer, so newlines arrive as
+    /// part of one bracketed paste instead of as separate Enter presses.
+    /// Each call uses its own buffer, deleted by the paste, so concurrent
+    /// sends (e.g. the daemon and a TUI) can't paste each other's text.
+    pub fn paste_text(target: &str, text: &str) -> Result<()> {
+        static PASTES: AtomicU64 = AtomicU64::new(0);
+        let buffer = format!(
+            "tsm-send-{}-{}",
+            std::process::id(),
+            PASTES.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let mut child = Command::new("tmux")
+            .args(["load-buffer", "-b", &buffer, "-"])
+            .stdin(Stdio::piped())
+            .spawn()
+            .context("Failed to load tmux buffer")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if !child.wait()?.success() {
+            anyhow::bail!("Failed to load tmux buffer");
+        }
+
+        let status = Command::new("tmux")
+            .args(["paste-buffer", "-d", "-p", "-b", &buffer, "-t", target])
+            .status()
+            .context("Failed to paste buffer")?;
+
+        if !status.success() {
+            let _ = Command::new("tmux")
+                .args(["delete-buffer", "-b", &buffer])
+                .status();
+            anyhow::bail!("Failed to paste into {}", target);
+        }
+
+        Ok(())
+    }
+
+    /// Mute or unmute notifications for a session via its `@tsm-muted` option
+    pub fn set_session_muted(session: &str, muted: bool) -> Result<()> {
+        let mut command = Command::new("tmux");
+        if muted {
+            command.args(["set-option", "-t", session, "@tsm-muted", "1"]);
+        } else {
+            command.args(["set-option", "-u", "-t", session, "@tsm-muted"]);
+        }
+        let status = command.status().context("Failed to set session option")?;
+
+        if !status.success() {
+            anyhow::bail!("Failed to update notifications for session {}", session);
+        }
+
+        Ok(())
+    }
+
     /// Apply recommended tmux settings (mouse, titles, allow-rename)
     pub fn apply_settings() -> Result<()> {
         let settings = [
@@ -357,3 +462,96 @@ impl Tmux {
         }
     }
 }
+
+/// Pick the most recently active non-control client from `list-clients`
+/// output
+fn pick_user_client(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let activity: u64 = fields.next()?.parse().ok()?;
+            let control = fields.next()?;
+            let name = fields.next()?;
+            (control == "0").then(|| (activity, name.to_string()))
+        })
+        .max_by_key(|(activity, _)| *activity)
+        .map(|(_, name)| name)
+}
+
+/// Split text into `send-keys` arguments: words are looked up as key
+/// names (typed as text when they aren't one) and each space or tab is
+/// sent as its own key. tmux joins the arguments with nothing in between,
+/// so the whitespace has to be sent explicitly.
+fn text_keys(text: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        let key = match c {
+            ' ' => "Space",
+            '\t' => "Tab",
+            _ => {
+                word.push(c);
+                continue;
+            }
+        };
+        if !word.is_empty() {
+            keys.push(std::mem::take(&mut word));
+        }
+        keys.push(key.to_string());
+    }
+    if !word.is_empty() {
+        keys.push(word);
+    }
+    keys
+}
+
+/// Arguments for `tmux send-keys`. Keys follow `--` so text starting with
+/// `-` isn't read as a flag.
+fn send_keys_args<'a>(target: &'a str, keys: &'a [String], literal: bool) -> Vec<&'a str> {
+    let mut args = vec!["send-keys", "-t", target];
+    if literal {
+        args.push("-l");
+    }
+    args.push("--");
+    args.extend(keys.iter().map(String::as_str));
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_user_client() {
+        let output = "300\t1\tclient-9\n100\t0\t/dev/pts/1\n200\t0\t/dev/pts/2\n";
+        assert_eq!(pick_user_client(output).as_deref(), Some("/dev/pts/2"));
+        assert_eq!(pick_user_client("300\t1\tclient-9\n"), None);
+    }
+
+    #[test]
+    fn test_text_keys() {
+        let keys = text_keys("run the  tests");
+        let args = send_keys_args("%1", &keys, false);
+        assert_eq!(args[..4], ["send-keys", "-t", "%1", "--"]);
+        assert_eq!(
+            args[4..],
+            ["run", "Space", "the", "Space", "Space", "tests"]
+        );
+        assert_eq!(text_keys("C-c"), ["C-c"]);
+        assert!(text_keys("").is_empty());
+    }
+
+    #[test]
+    fn test_send_keys_args() {
+        let keys = vec!["-rf".to_string(), "Enter".to_string()];
+        assert_eq!(
+            send_keys_args("%1", &keys, false),
+            ["send-keys", "-t", "%1", "--", "-rf", "Enter"]
+        );
+        assert_eq!(
+            send_keys_args("%1", &keys[..1], true),
+            ["send-keys", "-t", "%1", "-l", "--", "-rf"]
+        );
+    }
+}
@@ -8,7 +8,12 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::{App, NewSessionField, SessionAction};
+use crate::app::{
+    App, BulkAction, BulkTarget, Destination, NewSessionField, SendTarget, SessionAction,
+    SessionResult,
+};
+use crate::detection::Menu;
+use crate::queue::PromptQueue;
 
 use super::help::centered_rect;
 
@@ -20,49 +25,53 @@ pub fn render_confirm_action(frame: &mut Frame, app: &App) {
         .as_ref()
         .is_some_and(|c| c == session_name);
 
-    match &app.pending_action {
-        Some(action) => {
-            let kills_session = matches!(action, SessionAction::Kill);
-            let show_exit_warning = kills_session && is_current_session;
-
-            let dialog_height = if show_exit_warning { 9 } else { 7 };
-            let area = centered_rect(59, dialog_height, frame.area());
-
-            let block = Block::default()
-                .title(" \u{f071} Confirm ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Red))
-                .padding(Padding::new(2, 2, 1, 0));
-
-            let mut lines = vec![Line::from(format!(
-                "{} '{}'?",
-                action.label(),
-                session_name
-            ))];
-
-            if show_exit_warning {
-                lines.push(Line::raw(""));
-                lines.push(Line::styled(
-                    "This is your current session - tmux will exit!",
-                    Style::default()
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ));
-            }
+    let Some(action) = &app.pending_action else {
+        return;
+    };
 
-            lines.push(Line::raw(""));
-            lines.push(Line::from("[Y]es  [n]o"));
+    // Window and pane kills name the node picked in the tree
+    let node = app.tree_node.as_ref();
+    let target_name = node.map_or(session_name, |node| node.label.as_str());
+    let kills_session = match action {
+        SessionAction::Kill => true,
+        SessionAction::KillWindow | SessionAction::KillPane => {
+            node.is_some_and(|node| node.last_in_session)
+        }
+        _ => false,
+    };
+    let show_exit_warning = kills_session && is_current_session;
 
-            let paragraph = Paragraph::new(Text::from(lines))
-                .block(block)
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true });
+    let dialog_height = if show_exit_warning { 9 } else { 7 };
+    let area = centered_rect(59, dialog_height, frame.area());
 
-            frame.render_widget(Clear, area);
-            frame.render_widget(paragraph, area);
-        }
-        None => {}
+    let block = Block::default()
+        .title(" \u{f071} Confirm ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let mut lines = vec![Line::from(format!("{} '{}'?", action.label(), target_name))];
+
+    if show_exit_warning {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            "This is your current session - tmux will exit!",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
     }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::from("[Y]es  [n]o"));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
 }
 
 pub fn render_new_session_dialog(
@@ -246,8 +255,7 @@ pub fn render_new_session_dialog(
             ));
         }
 
-        for i in start..end {
-            let suggestion = &path_suggestions[i];
+        for (i, suggestion) in path_suggestions.iter().enumerate().take(end).skip(start) {
             let is_selected = path_selected == Some(i);
             let prefix = if is_selected { "    > " } else { "      " };
             let style = if is_selected {
@@ -317,3 +325,514 @@ pub fn render_rename_dialog(frame: &mut Frame, old_name: &str, new_name: &str) {
     frame.render_widget(Clear, area);
     frame.render_widget(paragraph, area);
 }
+
+pub fn render_new_window_dialog(
+    frame: &mut Frame,
+    session_name: &str,
+    name: &str,
+    start_claude: bool,
+) {
+    let area = centered_rect(54, 9, frame.area());
+
+    let block = Block::default()
+        .title(format!(" \u{f067} New window in '{}' ", session_name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let (claude_style, shell_style) = if start_claude {
+        (
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            Style::default().fg(Color::DarkGray),
+        )
+    } else {
+        (
+            Style::default().fg(Color::DarkGray),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+
+    let text = Text::from(vec![
+        Line::from(vec![
+            Span::raw("Start: "),
+            Span::styled(
+                if start_claude {
+                    "[\u{f0e4e} Claude]"
+                } else {
+                    " \u{f0e4e} Claude "
+                },
+                claude_style,
+            ),
+            Span::raw("  "),
+            Span::styled(
+                if !start_claude {
+                    "[\u{f489} Shell]"
+                } else {
+                    " \u{f489} Shell "
+                },
+                shell_style,
+            ),
+        ]),
+        Line::raw(""),
+        Line::from(vec![
+            Span::raw("Name: "),
+            Span::styled(name, Style::default().fg(Color::Yellow)),
+            Span::raw("_"),
+        ]),
+        Line::raw(""),
+        Line::styled(
+            "Enter create  Tab claude/shell",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]);
+
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_pick_session_dialog(
+    frame: &mut Frame,
+    action: &SessionAction,
+    node_label: &str,
+    destinations: &[Destination],
+    selected: usize,
+) {
+    // Keep the highlighted session in view
+    let start = (selected + 1).saturating_sub(MAX_LISTED);
+    let shown = &destinations[start..destinations.len().min(start + MAX_LISTED)];
+    let area = centered_rect(54, shown.len() as u16 + 7, frame.area());
+
+    let block = Block::default()
+        .title(format!(" {} '{}' ", action.label(), node_label))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let prompt = match action {
+        SessionAction::BreakPane => "Into a new window of:",
+        SessionAction::JoinPane => "Into the current window of:",
+        _ => "To session:",
+    };
+    let mut lines = vec![Line::raw(prompt), Line::raw("")];
+    for (i, destination) in shown.iter().enumerate() {
+        let is_selected = start + i == selected;
+        let marker = if is_selected { "\u{f054}" } else { " " };
+        let style = if is_selected {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::styled(
+            format!("{} {}", marker, destination.session_name),
+            style,
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "Enter move  Esc cancel",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_send_text_dialog(
+    frame: &mut Frame,
+    title: &str,
+    text: &str,
+    enter: bool,
+    literal: bool,
+    broadcast: bool,
+) {
+    let text_lines: Vec<&str> = text.split('\n').collect();
+    let shown = text_lines.len().min(8);
+    let hint_lines = if broadcast { 1 } else { 0 };
+    let area = centered_rect(64, shown as u16 + hint_lines + 6, frame.area());
+
+    let block = Block::default()
+        .title(format!(" \u{f1d8} {} ", title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    // Keep the end of long input, where the cursor is, in view
+    let mut lines: Vec<Line> = text_lines[text_lines.len() - shown..]
+        .iter()
+        .map(|line| Line::styled(*line, Style::default().fg(Color::Yellow)))
+        .collect();
+    if let Some(last) = lines.last_mut() {
+        last.push_span(Span::raw("_"));
+    }
+
+    let toggle = |on: bool| if on { "\u{f046}" } else { "\u{f096}" };
+    let mode = match text_lines.len() {
+        1 => "1 line as keys".to_string(),
+        n => format!("{} lines as paste", n),
+    };
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        format!(
+            "{} Enter (^E)  {} Literal (^L)  {}",
+            toggle(enter),
+            toggle(literal),
+            mode
+        ),
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    if broadcast {
+        lines.push(Line::styled(
+            "Tab: marked / group / filter matches",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+/// Rows listed in the broadcast and queue dialogs
+const MAX_LISTED: usize = 12;
+
+pub fn render_confirm_broadcast(frame: &mut Frame, text: &str, targets: &[SendTarget]) {
+    let listed = targets.len().min(MAX_LISTED);
+    let more = targets.len() - listed;
+    let height = listed + usize::from(more > 0) + 8;
+    let area = centered_rect(64, height as u16, frame.area());
+
+    let block = Block::default()
+        .title(" \u{f071} Confirm broadcast ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let first_line = text.lines().next().unwrap_or("");
+    let extra_lines = text.lines().count().saturating_sub(1);
+    let mut sent = vec![Span::styled(first_line, Style::default().fg(Color::Yellow))];
+    if extra_lines > 0 {
+        sent.push(Span::styled(
+            format!(" (+{} lines)", extra_lines),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let mut lines = vec![
+        Line::from(format!("Send to {} sessions?", targets.len())),
+        Line::from(sent),
+        Line::raw(""),
+    ];
+    for target in &targets[..listed] {
+        lines.push(Line::from(format!("  \u{f054} {}", target.session_name)));
+    }
+    if more > 0 {
+        lines.push(Line::styled(
+            format!("  … and {} more", more),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from("[Y]es  [n]o"));
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_bulk_rename_dialog(
+    frame: &mut Frame,
+    pattern: &str,
+    targets: Result<Vec<BulkTarget>, String>,
+) {
+    let rows = match targets {
+        Ok(ref targets) => targets.len().min(MAX_LISTED),
+        Err(_) => 1,
+    };
+    let area = centered_rect(64, rows as u16 + 7, frame.area());
+
+    let block = Block::default()
+        .title(" \u{f040} Rename marked sessions ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw("Pattern: "),
+            Span::styled(pattern, Style::default().fg(Color::Yellow)),
+            Span::raw("_"),
+        ]),
+        Line::raw(""),
+    ];
+    match targets {
+        Ok(targets) => {
+            for target in targets.iter().take(MAX_LISTED) {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("  {} → ", target.session_name),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(target.new_name.clone().unwrap_or_default()),
+                ]));
+            }
+        }
+        Err(e) => lines.push(Line::styled(e, Style::default().fg(Color::Red))),
+    }
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_confirm_bulk(
+    frame: &mut Frame,
+    app: &App,
+    action: BulkAction,
+    targets: &[BulkTarget],
+) {
+    let listed = targets.len().min(MAX_LISTED);
+    let more = targets.len() - listed;
+    let kills_current = action == BulkAction::Kill
+        && targets
+            .iter()
+            .any(|t| app.current_session.as_ref() == Some(&t.session_name));
+    let height = listed + usize::from(more > 0) + usize::from(kills_current) * 2 + 7;
+    let area = centered_rect(64, height as u16, frame.area());
+
+    let block = Block::default()
+        .title(" \u{f071} Confirm ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let mut lines = vec![
+        Line::from(format!("{} {} sessions?", action.verb(), targets.len())),
+        Line::raw(""),
+    ];
+    for target in &targets[..listed] {
+        lines.push(Line::from(match target.new_name {
+            Some(ref new_name) => format!("  \u{f054} {} → {}", target.session_name, new_name),
+            None => format!("  \u{f054} {}", target.session_name),
+        }));
+    }
+    if more > 0 {
+        lines.push(Line::styled(
+            format!("  … and {} more", more),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if kills_current {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            "This includes your current session - tmux will exit!",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::from("[Y]es  [n]o"));
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_bulk_results(frame: &mut Frame, title: &str, results: &[SessionResult]) {
+    // Failures first, they are what needs attention
+    let mut sorted: Vec<&SessionResult> = results.iter().collect();
+    sorted.sort_by_key(|r| r.error.is_none());
+
+    let listed = sorted.len().min(MAX_LISTED);
+    let more = sorted.len() - listed;
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    let height = listed + usize::from(more > 0) + 4;
+    let area = centered_rect(72, height as u16, frame.area());
+
+    let color = if failed == 0 {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    let block = Block::default()
+        .title(format!(
+            " {} {}/{} sessions ",
+            title,
+            results.len() - failed,
+            results.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let mut lines: Vec<Line> = sorted[..listed]
+        .iter()
+        .map(|result| match result.error {
+            None => Line::from(vec![
+                Span::styled("\u{f00c} ", Style::default().fg(Color::Green)),
+                Span::raw(result.session_name.as_str()),
+            ]),
+            Some(ref error) => Line::from(vec![
+                Span::styled("\u{f00d} ", Style::default().fg(Color::Red)),
+                Span::raw(result.session_name.as_str()),
+                Span::styled(format!("  {}", error), Style::default().fg(Color::DarkGray)),
+            ]),
+        })
+        .collect();
+    if more > 0 {
+        lines.push(Line::styled(
+            format!("… and {} more", more),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_queue_dialog(
+    frame: &mut Frame,
+    session_name: &str,
+    queue: Option<&PromptQueue>,
+    selected: usize,
+    input: Option<&str>,
+) {
+    let prompts = queue.map_or(&[][..], |queue| queue.prompts.as_slice());
+    let paused = queue.is_some_and(|queue| queue.paused);
+    let input_lines = input.map_or(0, |input| input.split('\n').count() + 1);
+    let height = prompts.len().clamp(1, MAX_LISTED) + input_lines + 6;
+    let area = centered_rect(72, height as u16, frame.area());
+
+    let title = if paused {
+        format!(" \u{f04c} Queue for '{}' (paused) ", session_name)
+    } else {
+        format!(" \u{f0cb} Queue for '{}' ", session_name)
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let mut lines = Vec::new();
+    if prompts.is_empty() {
+        lines.push(Line::styled(
+            "No queued prompts",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    // Scroll so the highlighted prompt stays visible
+    let first = selected.saturating_sub(MAX_LISTED - 1);
+    for (i, prompt) in prompts.iter().enumerate().skip(first).take(MAX_LISTED) {
+        let is_selected = i == selected && input.is_none();
+        let marker = if is_selected { "\u{f054}" } else { " " };
+        let style = if is_selected {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let mut text = prompt.lines().next().unwrap_or("").to_string();
+        let extra = prompt.lines().count().saturating_sub(1);
+        if extra > 0 {
+            text.push_str(&format!(" (+{} lines)", extra));
+        }
+        lines.push(Line::styled(
+            format!("{} {}. {}", marker, i + 1, text),
+            style,
+        ));
+    }
+
+    if let Some(input) = input {
+        lines.push(Line::raw(""));
+        let mut input_lines: Vec<Line> = input
+            .split('\n')
+            .map(|line| Line::styled(line, Style::default().fg(Color::Yellow)))
+            .collect();
+        if let Some(last) = input_lines.last_mut() {
+            last.push_span(Span::raw("_"));
+        }
+        input_lines[0].spans.insert(0, Span::raw("New prompt: "));
+        lines.extend(input_lines);
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "Sent one at a time when the agent goes from working to idle",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let paragraph = Paragraph::new(Text::from(lines)).block(block);
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+pub fn render_quick_reply_dialog(
+    frame: &mut Frame,
+    session_name: &str,
+    menu: &Menu,
+    selected: usize,
+) {
+    let question_lines = if menu.question.is_some() { 2 } else { 0 };
+    let height = menu.options.len() as u16 + question_lines + 6;
+    let area = centered_rect(64, height, frame.area());
+
+    let block = Block::default()
+        .title(format!(" \u{f075} Reply to '{}' ", session_name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .padding(Padding::new(2, 2, 1, 0));
+
+    let mut lines = Vec::new();
+    if let Some(ref question) = menu.question {
+        lines.push(Line::styled(
+            question.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::raw(""));
+    }
+
+    for (i, option) in menu.options.iter().enumerate() {
+        let is_selected = i == selected;
+        let marker = if is_selected { "\u{f054}" } else { " " };
+        let style = if is_selected {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::styled(
+            format!("{} {}. {}", marker, option.number, option.label),
+            style,
+        ));
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        "Enter send  0-9 type number  Esc cancel",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
@@ -9,7 +9,7 @@ use ratatui::{
 };
 
 pub fn render_help(frame: &mut Frame) {
-    let area = centered_rect(64, 26, frame.area());
+    let area = centered_rect(64, 48, frame.area());
 
     let block = Block::default()
         .title(" \u{f059} Help ")
@@ -26,6 +26,8 @@ pub fn render_help(frame: &mut Frame) {
         Line::raw("  k / ↑       Move up"),
         Line::raw("  l / →       Open action menu"),
         Line::raw("  Enter       Switch to session"),
+        Line::raw("  t           Browse windows and panes"),
+        Line::raw("  v           Read pane history (/ search, n/N, g/G)"),
         Line::raw(""),
         Line::from(Span::styled(
             "\u{f0e7} Actions",
@@ -34,10 +36,25 @@ pub fn render_help(frame: &mut Frame) {
         Line::raw("  n           New session"),
         Line::raw("  K           Kill session"),
         Line::raw("  r           Rename session"),
+        Line::raw("  a           Answer a waiting prompt (1. Yes / 2. No)"),
+        Line::raw("  s           Send text to the agent"),
+        Line::raw("  Q           Prompt queue (sent when the agent goes idle)"),
+        Line::raw("  d           Detach clients"),
+        Line::raw("  B           Broadcast text to marked / group / filter"),
         Line::raw("  /           Filter sessions"),
+        Line::raw("  o           Sort: created / waiting longest"),
         Line::raw("  R           Refresh list"),
         Line::raw("  S           Apply tmux settings"),
         Line::raw(""),
+        Line::from(Span::styled(
+            "\u{f00c} Marking",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::raw("  m           Mark / unmark session"),
+        Line::raw("  M / *       Mark group / all filter matches"),
+        Line::raw("  u           Unmark all"),
+        Line::raw("  K s r d     Kill / send / rename / detach marked"),
+        Line::raw(""),
         Line::from(Span::styled(
             "\u{f03a} Action Menu",
             Style::default().add_modifier(Modifier::BOLD),
@@ -45,6 +62,17 @@ pub fn render_help(frame: &mut Frame) {
         Line::raw("  h / ←       Go back"),
         Line::raw("  Enter       Execute action"),
         Line::raw(""),
+        Line::from(Span::styled(
+            "\u{f2d0} Window Tree",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::raw("  Enter / ␣   Switch to window or pane"),
+        Line::raw("  n           New window"),
+        Line::raw("  x / K       Kill window or pane"),
+        Line::raw("  r / m       Rename / move window"),
+        Line::raw("  b / J       Break pane out / join into a session"),
+        Line::raw("  v           Read pane history"),
+        Line::raw(""),
         Line::from(Span::styled(
             "\u{f013} Other",
             Style::default().add_modifier(Modifier::BOLD),
@@ -65,11 +93,11 @@ pub fn render_message(frame: &mut Frame, message: &str, color: Color) {
     let area = frame.area();
 
     let max_width = area.width.saturating_sub(6) as usize;
-    let lines_needed = if max_width > 0 {
-        (message.len() / max_width + 1).min(3)
-    } else {
-        1
-    };
+    let lines_needed = message
+        .len()
+        .checked_div(max_width)
+        .map(|n| (n + 1).min(3))
+        .unwrap_or(1);
     let height = lines_needed as u16;
 
     let msg_area = Rect {
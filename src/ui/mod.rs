@@ -13,8 +13,10 @@ use ratatui::{
 };
 use unicode_width::UnicodeWidthStr;
 
-use crate::app::{App, Mode};
-use crate::session::ClaudeCodeStatus;
+use crate::agents::Agent;
+use crate::app::{App, Mode, SendTo, SortMode};
+use crate::queue;
+use crate::session::{format_elapsed, unix_now, ClaudeCodeStatus, TreeNode};
 
 /// Render the application UI
 pub fn render(frame: &mut Frame, app: &mut App) {
@@ -28,9 +30,22 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     // Auto-hide preview on small terminals
     let show_preview = app.show_preview && area.height >= 30;
 
-    let status_bar_area;
+    let status_bar_area = if matches!(app.mode, Mode::Preview { .. }) {
+        // The pager takes the place of the list and preview
+        let layout = Layout::vertical([
+            Constraint::Length(1), // Header
+            Constraint::Min(3),    // Pager
+            Constraint::Length(1), // Status bar
+            Constraint::Length(1), // Footer
+        ])
+        .split(area);
 
-    if show_preview {
+        render_header(frame, app, layout[0]);
+        render_pager(frame, app, layout[1]);
+        render_status_bar(frame, app, layout[2]);
+        render_footer(frame, app, layout[3]);
+        layout[2]
+    } else if show_preview {
         let available_height = area.height.saturating_sub(4);
         let preview_height = (available_height * 50 / 100).clamp(5, 30);
 
@@ -48,7 +63,7 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         render_preview(frame, app, layout[2]);
         render_status_bar(frame, app, layout[3]);
         render_footer(frame, app, layout[4]);
-        status_bar_area = layout[3];
+        layout[3]
     } else {
         let layout = Layout::vertical([
             Constraint::Length(1), // Header
@@ -62,8 +77,8 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         render_session_list(frame, app, layout[1]);
         render_status_bar(frame, app, layout[2]);
         render_footer(frame, app, layout[3]);
-        status_bar_area = layout[2];
-    }
+        layout[2]
+    };
 
     // Render modal overlays
     match &app.mode {
@@ -91,13 +106,94 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         Mode::Rename { old_name, new_name } => {
             dialogs::render_rename_dialog(frame, old_name, new_name);
         }
+        Mode::NewWindow { name, start_claude } => {
+            let session_name = app.selected_session().map_or("?", |s| s.name.as_str());
+            dialogs::render_new_window_dialog(frame, session_name, name, *start_claude);
+        }
+        Mode::RenameWindow { new_name } => {
+            let label = app
+                .tree_node
+                .as_ref()
+                .map_or("?", |node| node.label.as_str());
+            dialogs::render_rename_dialog(frame, label, new_name);
+        }
+        Mode::PickSession {
+            action,
+            destinations,
+            selected,
+        } => {
+            let label = app
+                .tree_node
+                .as_ref()
+                .map_or("?", |node| node.label.as_str());
+            dialogs::render_pick_session_dialog(frame, action, label, destinations, *selected);
+        }
         Mode::Filter { input } => {
             render_filter_bar(frame, input, status_bar_area);
         }
+        Mode::Preview {
+            search: Some(query),
+        } => {
+            frame.render_widget(Clear, status_bar_area);
+            let bar = Paragraph::new(format!("  \u{f002} / {}", query))
+                .style(Style::default().fg(Color::Yellow));
+            frame.render_widget(bar, status_bar_area);
+        }
+        Mode::QuickReply {
+            session_name,
+            menu,
+            selected,
+            ..
+        } => {
+            dialogs::render_quick_reply_dialog(frame, session_name, menu, *selected);
+        }
+        Mode::SendText {
+            to,
+            text,
+            enter,
+            literal,
+        } => {
+            let title = match to {
+                SendTo::Session(to) => format!("Send to '{}'", to.session_name),
+                SendTo::Broadcast(scope) => format!(
+                    "Broadcast to {} ({})",
+                    app.broadcast_scope_label(*scope),
+                    app.broadcast_targets(*scope).len()
+                ),
+            };
+            let broadcast = matches!(to, SendTo::Broadcast(_));
+            dialogs::render_send_text_dialog(frame, &title, text, *enter, *literal, broadcast);
+        }
+        Mode::ConfirmBroadcast { text, targets, .. } => {
+            dialogs::render_confirm_broadcast(frame, text, targets);
+        }
+        Mode::BulkRename { pattern } => {
+            dialogs::render_bulk_rename_dialog(frame, pattern, app.bulk_rename_targets(pattern));
+        }
+        Mode::ConfirmBulk { action, targets } => {
+            dialogs::render_confirm_bulk(frame, app, *action, targets);
+        }
+        Mode::BulkResults { title, results } => {
+            dialogs::render_bulk_results(frame, title, results);
+        }
+        Mode::Queue {
+            key,
+            session_name,
+            selected,
+            input,
+        } => {
+            dialogs::render_queue_dialog(
+                frame,
+                session_name,
+                app.queues.get(key),
+                *selected,
+                input.as_deref(),
+            );
+        }
         Mode::Help => {
             help::render_help(frame);
         }
-        Mode::Normal | Mode::ActionMenu => {}
+        Mode::Normal | Mode::ActionMenu | Mode::Tree { .. } | Mode::Preview { .. } => {}
     }
 
     // Render error/message overlay
@@ -140,7 +236,9 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
     let filtered = app.filtered_sessions();
 
     if filtered.is_empty() {
-        let empty_msg = if app.filter.is_empty() {
+        let empty_msg = if app.last_refresh.is_none() {
+            "Loading sessions..."
+        } else if app.filter.is_empty() {
             "No tmux sessions found. Press 'n' to create one."
         } else {
             "No sessions match the filter."
@@ -162,6 +260,7 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
         .max(10);
 
     let groups = app.grouped_sessions();
+    let now = unix_now();
 
     let mut items: Vec<ListItem> = Vec::new();
 
@@ -187,7 +286,8 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 .as_ref()
                 .is_some_and(|c| c == &session.name);
 
-            let is_expanded = is_selected && matches!(app.mode, Mode::ActionMenu);
+            let is_expanded =
+                is_selected && matches!(app.mode, Mode::ActionMenu | Mode::Tree { .. });
             let marker = if is_selected {
                 if is_expanded { "\u{f078}" } else { "\u{f054}" }
             } else {
@@ -195,25 +295,10 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
             };
             let status = &session.claude_code_status;
 
-            let session_icon = if session.claude_code_pane.is_some() {
-                "\u{f4f5}"
-            } else {
-                "\u{e691}"
-            };
-            let session_icon_color = if session.claude_code_pane.is_some() {
-                Color::Rgb(227, 137, 54)
-            } else {
-                Color::DarkGray
-            };
+            let session_icon = session.agent.map(|a| a.icon()).unwrap_or("\u{e691}");
+            let session_icon_color = session.agent.map(agent_color).unwrap_or(Color::DarkGray);
 
-            let status_color = match (status, is_selected) {
-                (ClaudeCodeStatus::Working, _) => Color::Green,
-                (ClaudeCodeStatus::WaitingInput, _) => Color::Yellow,
-                (ClaudeCodeStatus::Idle, true) => Color::White,
-                (ClaudeCodeStatus::Idle, false) => Color::DarkGray,
-                (ClaudeCodeStatus::Unknown, true) => Color::Gray,
-                (ClaudeCodeStatus::Unknown, false) => Color::DarkGray,
-            };
+            let status_color = status_color(status, is_selected);
 
             let name_style = if is_current {
                 Style::default().add_modifier(Modifier::BOLD)
@@ -222,9 +307,14 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
             };
 
             // Order: name / status / pane_title / path
-            let indent = " ";
+            let indent = if app.is_marked(session) {
+                Span::styled("\u{f00c}", Style::default().fg(Color::Magenta))
+            } else {
+                Span::raw(" ")
+            };
             let mut line_spans = vec![
-                Span::raw(format!("{}{} ", indent, marker)),
+                indent,
+                Span::raw(format!("{} ", marker)),
                 Span::styled(format!("{} ", session_icon), Style::default().fg(session_icon_color)),
                 Span::styled(
                     format!("{:<width$}", session.name, width = max_name_len),
@@ -234,13 +324,44 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 Span::styled(status.symbol(), Style::default().fg(status_color)),
                 Span::raw(" "),
                 Span::styled(
-                    format!("{:<8}", status.label()),
+                    format!("{:<8}", session.status_summary()),
+                    Style::default().fg(status_color),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!(
+                        "{:<6}",
+                        session
+                            .time_in_status(now)
+                            .map(format_elapsed)
+                            .unwrap_or_default()
+                    ),
                     Style::default().fg(status_color),
                 ),
             ];
 
-            // Pane title
-            if !session.pane_title.is_empty() {
+            // Prompts waiting to be sent when the agent goes idle
+            if let Some(queue) = app.queues.get(&queue::key(session)) {
+                let (icon, color) = if queue.paused {
+                    ("\u{f04c}", Color::DarkGray)
+                } else {
+                    ("\u{f0cb}", Color::Cyan)
+                };
+                line_spans.push(Span::raw("  "));
+                line_spans.push(Span::styled(
+                    format!("{} {}", icon, queue.prompts.len()),
+                    Style::default().fg(color),
+                ));
+            }
+
+            // What a waiting agent asks, else the pane title
+            if let Some(question) = session.pending_question() {
+                line_spans.push(Span::raw("  "));
+                line_spans.push(Span::styled(
+                    format!("? {}", question.summary()),
+                    Style::default().fg(Color::Yellow),
+                ));
+            } else if !session.pane_title.is_empty() {
                 let title_color = if is_selected {
                     Color::Cyan
                 } else {
@@ -280,7 +401,29 @@ fn render_session_list(frame: &mut Frame, app: &mut App, area: Rect) {
     app.scroll_state = scroll_state;
 }
 
-/// Render the expanded content for a session in action menu mode
+/// Color for an agent status
+fn status_color(status: &ClaudeCodeStatus, is_selected: bool) -> Color {
+    match (status, is_selected) {
+        (ClaudeCodeStatus::Working, _) => Color::Green,
+        (ClaudeCodeStatus::WaitingInput, _) => Color::Yellow,
+        (ClaudeCodeStatus::Idle, true) => Color::White,
+        (ClaudeCodeStatus::Idle, false) => Color::DarkGray,
+        (ClaudeCodeStatus::Unknown, true) => Color::Gray,
+        (ClaudeCodeStatus::Unknown, false) => Color::DarkGray,
+    }
+}
+
+/// Brand color for an agent's icon
+fn agent_color(agent: Agent) -> Color {
+    match agent {
+        Agent::Claude => Color::Rgb(227, 137, 54),
+        Agent::Codex => Color::Rgb(16, 163, 127),
+        Agent::Aider => Color::Rgb(20, 178, 20),
+        Agent::Gemini => Color::Rgb(66, 133, 244),
+    }
+}
+
+/// Render the expanded content for a session in action menu and tree mode
 fn render_expanded_session_content<'a>(
     app: &'a App,
     session: &'a crate::session::Session,
@@ -293,7 +436,7 @@ fn render_expanded_session_content<'a>(
     let attached_str = if session.attached { "yes" } else { "no" };
     let pane_count = session.panes.len();
 
-    let meta_line = Line::from(vec![
+    let mut meta_spans = vec![
         Span::raw("   "),
         Span::styled("\u{f2d0} windows: ", label_style),
         Span::styled(format!("{}", session.window_count), value_style),
@@ -306,8 +449,88 @@ fn render_expanded_session_content<'a>(
         Span::raw("  "),
         Span::styled("\u{f0c1} attached: ", label_style),
         Span::styled(attached_str, value_style),
-    ]);
-    items.push(ListItem::new(meta_line));
+    ];
+    if let Some(agent) = session.agent {
+        meta_spans.push(Span::raw("  "));
+        meta_spans.push(Span::styled(
+            format!("{} agent: ", agent.icon()),
+            label_style,
+        ));
+        meta_spans.push(Span::styled(agent.label(), value_style));
+    }
+    items.push(ListItem::new(Line::from(meta_spans)));
+
+    // The session's windows, each followed by its panes
+    let tree_selected = app.tree_selection();
+    for (node_idx, node) in session.tree().into_iter().enumerate() {
+        let is_node_selected = tree_selected == Some(node_idx);
+        let node_marker = if is_node_selected { "\u{f054}" } else { " " };
+        let name_style = if is_node_selected {
+            Style::default().fg(Color::Yellow)
+        } else {
+            value_style
+        };
+        let active_span = |active: bool| {
+            Span::styled(
+                if active { " *" } else { "" },
+                Style::default().fg(Color::Cyan),
+            )
+        };
+
+        match node {
+            TreeNode::Window {
+                index,
+                name,
+                active,
+            } => {
+                items.push(ListItem::new(Line::from(vec![
+                    Span::raw("   "),
+                    Span::styled(node_marker, name_style),
+                    Span::styled(format!(" \u{f2d0} {}:", index), label_style),
+                    Span::styled(format!(" {}", name), name_style),
+                    active_span(active),
+                ])));
+            }
+            TreeNode::Pane(pane) => {
+                let mut pane_spans = vec![
+                    Span::raw("   "),
+                    Span::styled(node_marker, name_style),
+                    Span::raw("   "),
+                    Span::styled(format!("{:<4}", pane.id), label_style),
+                    Span::raw(" "),
+                    Span::styled(format!("{:<10}", pane.current_command), name_style),
+                    Span::raw(" "),
+                    Span::styled(pane.display_path(), label_style),
+                ];
+                if pane.agent.is_some() {
+                    let color = status_color(&pane.status, true);
+                    pane_spans.push(Span::raw("  "));
+                    pane_spans.push(Span::styled(
+                        format!("{} {}", pane.status.symbol(), pane.status.label()),
+                        Style::default().fg(color),
+                    ));
+                }
+                pane_spans.push(active_span(pane.active));
+                items.push(ListItem::new(Line::from(pane_spans)));
+
+                // The full question under a waiting pane
+                if let Some(ref question) = pane.question {
+                    for text in question.text.lines() {
+                        items.push(ListItem::new(Line::from(vec![
+                            Span::raw("          "),
+                            Span::styled(text, Style::default().fg(Color::Yellow)),
+                        ])));
+                    }
+                }
+            }
+        }
+    }
+
+    // The tree replaces the actions while browsing it
+    if tree_selected.is_some() {
+        items.push(ListItem::new(Line::from("")));
+        return;
+    }
 
     // Separator
     let sep_line = Line::from(Span::styled(
@@ -376,43 +599,119 @@ fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(preview, inner);
 }
 
+/// Render a pane's history in preview mode
+fn render_pager(frame: &mut Frame, app: &mut App, area: Rect) {
+    let Some(ref mut pager) = app.pager else {
+        return;
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .padding(Padding::new(1, 1, 0, 0));
+    let inner = block.inner(area);
+    pager.set_height(inner.height as usize);
+
+    // Position, and the highlighted match while searching
+    let total = pager.line_count();
+    let last = (pager.top() + inner.height as usize).min(total);
+    let mut position = format!(" {}-{}/{} ", (pager.top() + 1).min(total), last, total);
+    if let Some(status) = pager.match_status() {
+        position = format!(" \u{f002} {}: {} │{}", pager.query, status, position);
+    }
+    let block = block
+        .title(format!(" \u{f06e} {} ", pager.title))
+        .title_bottom(Line::from(position).right_aligned());
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(block, area);
+    frame.render_widget(Paragraph::new(pager.visible_lines()), inner);
+}
+
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let (working, waiting, _idle) = app.status_counts();
-    let total = app.sessions.len();
+    let counts = app.status_counts();
 
-    let mut parts = vec![format!("\u{f489} {} sessions", total)];
+    let mut parts = vec![format!("\u{f489} {} sessions", counts.sessions)];
 
-    if working > 0 {
-        parts.push(format!("\u{f013} {} working", working));
+    if counts.working > 0 {
+        parts.push(format!("\u{f013} {} working", counts.working));
     }
-    if waiting > 0 {
-        parts.push(format!("\u{f0a6} {} awaiting input", waiting));
+    if counts.waiting > 0 {
+        parts.push(format!("\u{f0a6} {} awaiting input", counts.waiting));
     }
     let status = parts.join(" \u{2502} ");
 
-    let filter_info = if !app.filter.is_empty() {
+    let mut filter_info = if !app.filter.is_empty() {
         format!(" \u{2502} \u{f0b0} filter: \"{}\"", app.filter)
     } else {
         String::new()
     };
+    if !app.marked.is_empty() {
+        filter_info.push_str(&format!(" \u{2502} \u{f00c} {} marked", app.marked.len()));
+    }
+    if app.sort_mode != SortMode::default() {
+        filter_info.push_str(&format!(
+            " \u{2502} \u{f0dc} sort: {}",
+            app.sort_mode.label()
+        ));
+    }
+
+    let age_info = if app.from_daemon {
+        " \u{2502} \u{f0450} daemon".to_string()
+    } else if app.live_updates {
+        " \u{2502} \u{f0450} live".to_string()
+    } else {
+        app.data_age()
+            .map(|age| format!(" \u{2502} \u{f0450} {}", format_age(age)))
+            .unwrap_or_default()
+    };
 
-    let text = format!("  {}{}", status, filter_info);
+    let text = format!("  {}{}{}", status, filter_info, age_info);
 
     let bar = Paragraph::new(text).style(Style::default().fg(Color::DarkGray));
 
     frame.render_widget(bar, area);
 }
 
+/// Format how old the session data is, e.g. "0.3s ago" or "2m ago"
+fn format_age(age: std::time::Duration) -> String {
+    let secs = age.as_secs_f64();
+    if secs < 10.0 {
+        format!("{:.1}s ago", secs)
+    } else if secs < 60.0 {
+        format!("{}s ago", secs as u64)
+    } else {
+        format!("{}m ago", secs as u64 / 60)
+    }
+}
+
 fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
     let hints = match app.mode {
         Mode::Normal => {
-            "  \u{f059} ? help  \u{f14e} jk navigate  \u{f03a} l actions  \u{f064} ⏎ switch  \u{f06e} ␣ peek  \u{f067} n new  \u{f00d} K kill  \u{f0b0} / filter  \u{f013} S settings  q quit"
+            "  \u{f059} ? help  \u{f14e} jk navigate  \u{f03a} l actions  \u{f064} ⏎ switch  \u{f06e} ␣ peek  \u{f067} n new  \u{f00d} K kill  \u{f075} a reply  \u{f1d8} s send  B broadcast  Q queue  m mark  \u{f2d0} t windows  \u{f0b0} / filter  \u{f0dc} o sort  \u{f013} S settings  q quit"
         }
-        Mode::ActionMenu => "  \u{f14e} jk navigate  ⏎/l select  h/esc back  q quit",
+        Mode::ActionMenu => "  \u{f14e} jk navigate  ⏎/l select  t windows  h/esc back  q quit",
+        Mode::Tree { .. } => "  \u{f14e} jk navigate  \u{f064} ⏎ switch  \u{f06e} ␣ peek  n new window  x kill  r rename  m move  b break  J join  v history  h/esc back",
         Mode::Filter { .. } => "  ⏎ apply  esc cancel",
         Mode::ConfirmAction => "  \u{f00c} y/⏎ confirm  \u{f00d} n/esc cancel",
         Mode::NewSession { .. } => "  \u{f067} ⏎ create  tab switch  ↑↓ select  → accept  esc cancel",
-        Mode::Rename { .. } => "  \u{f040} ⏎ confirm  esc cancel",
+        Mode::Rename { .. } | Mode::RenameWindow { .. } => "  \u{f040} ⏎ confirm  esc cancel",
+        Mode::NewWindow { .. } => "  \u{f067} ⏎ create  tab claude/shell  esc cancel",
+        Mode::PickSession { .. } => "  \u{f14e} jk navigate  ⏎ move  esc cancel",
+        Mode::QuickReply { .. } => "  \u{f14e} jk navigate  ⏎ send  0-9 type number  esc cancel",
+        Mode::SendText {
+            to: SendTo::Broadcast(_),
+            ..
+        } => "  \u{f1d8} ⏎ send  tab targets  alt+⏎/^J newline  ^E enter  ^L literal  esc cancel",
+        Mode::SendText { .. } => "  \u{f1d8} ⏎ send  alt+⏎/^J newline  ^E enter  ^L literal  esc cancel",
+        Mode::ConfirmBroadcast { .. } => "  \u{f00c} y/⏎ send  \u{f00d} n/esc cancel",
+        Mode::BulkRename { .. } => "  \u{f040} ⏎ confirm  {name} name  {n} number  esc cancel",
+        Mode::ConfirmBulk { .. } => "  \u{f00c} y/⏎ confirm  \u{f00d} n/esc cancel",
+        Mode::BulkResults { .. } => "  any key close",
+        Mode::Queue { input: Some(_), .. } => "  \u{f0cb} ⏎ add  alt+⏎/^J newline  esc cancel",
+        Mode::Queue { .. } => "  \u{f0cb} jk navigate  JK reorder  a add  d delete  p pause  esc close",
+        Mode::Preview { search: Some(_) } => "  \u{f002} ⏎ search  esc cancel",
+        Mode::Preview { .. } => "  \u{f14e} jk scroll  PgUp/PgDn page  g/G top/bottom  / search  n/N next/prev  q close",
         Mode::Help => "  q close",
     };
 
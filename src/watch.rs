@@ -0,0 +1,256 @@
+//! Stream of session events for logs and automation
+//!
+//! `tsm watch` lists sessions at a fixed interval and prints one JSON
+//! object per line for every difference between two consecutive lists.
+//! Sessions come from the daemon's snapshot while a daemon is running.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::daemon;
+use crate::discovery::Discovery;
+use crate::session::{unix_now, ClaudeCodeStatus, Session};
+
+/// Interval between two session lists unless `--interval` is given
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// One line of `tsm watch` output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Event {
+    /// Unix timestamp of the list that showed the change
+    pub time: u64,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+/// What changed, tagged by the `event` field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    Created {
+        session_id: String,
+        session_name: String,
+    },
+    Killed {
+        session_id: String,
+        session_name: String,
+    },
+    Renamed {
+        session_id: String,
+        old_name: String,
+        new_name: String,
+    },
+    /// Status change of an agent pane
+    Status {
+        session_id: String,
+        session_name: String,
+        pane_id: String,
+        old: ClaudeCodeStatus,
+        new: ClaudeCodeStatus,
+    },
+    Attached {
+        session_id: String,
+        session_name: String,
+    },
+    Detached {
+        session_id: String,
+        session_name: String,
+    },
+    /// Title change of any pane
+    Title {
+        session_id: String,
+        session_name: String,
+        pane_id: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Print events until stdout is closed
+pub fn run(interval: Duration) -> Result<()> {
+    let mut discovery = Discovery::new(&Config::load()?)?;
+    let mut stdout = std::io::stdout();
+    let mut previous: Option<Vec<Session>> = None;
+
+    loop {
+        let sessions = match daemon::read_snapshot() {
+            Some(snapshot) => Ok(snapshot.sessions),
+            None => discovery.list_sessions(),
+        };
+        match sessions {
+            Ok(sessions) => {
+                if let Some(ref before) = previous {
+                    let time = unix_now();
+                    for kind in diff(before, &sessions) {
+                        let line = serde_json::to_string(&Event { time, kind })?;
+                        // The reader went away, e.g. `tsm watch | head`
+                        if writeln!(stdout, "{}", line).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    if stdout.flush().is_err() {
+                        return Ok(());
+                    }
+                }
+                previous = Some(sessions);
+            }
+            Err(e) => eprintln!("tsm watch: {:#}", e),
+        }
+
+        std::thread::sleep(interval);
+    }
+}
+
+/// Events that turn `before` into `after`. Sessions are matched by ID.
+pub fn diff(before: &[Session], after: &[Session]) -> Vec<EventKind> {
+    let old_sessions: HashMap<&str, &Session> = before.iter().map(|s| (s.id.as_str(), s)).collect();
+    let mut events = Vec::new();
+
+    for session in after {
+        let Some(old) = old_sessions.get(session.id.as_str()) else {
+            events.push(EventKind::Created {
+                session_id: session.id.clone(),
+                session_name: session.name.clone(),
+            });
+            continue;
+        };
+
+        if old.name != session.name {
+            events.push(EventKind::Renamed {
+                session_id: session.id.clone(),
+                old_name: old.name.clone(),
+                new_name: session.name.clone(),
+            });
+        }
+        if old.attached != session.attached {
+            let session_id = session.id.clone();
+            let session_name = session.name.clone();
+            events.push(if session.attached {
+                EventKind::Attached {
+                    session_id,
+                    session_name,
+                }
+            } else {
+                EventKind::Detached {
+                    session_id,
+                    session_name,
+                }
+            });
+        }
+
+        for pane in &session.panes {
+            let Some(old_pane) = old.panes.iter().find(|p| p.id == pane.id) else {
+                continue;
+            };
+            if pane.agent.is_some() && old_pane.status != pane.status {
+                events.push(EventKind::Status {
+                    session_id: session.id.clone(),
+                    session_name: session.name.clone(),
+                    pane_id: pane.id.clone(),
+                    old: old_pane.status,
+                    new: pane.status,
+                });
+            }
+            if old_pane.title != pane.title {
+                events.push(EventKind::Title {
+                    session_id: session.id.clone(),
+                    session_name: session.name.clone(),
+                    pane_id: pane.id.clone(),
+                    old: old_pane.title.clone(),
+                    new: pane.title.clone(),
+                });
+            }
+        }
+    }
+
+    for old in before {
+        if !after.iter().any(|s| s.id == old.id) {
+            events.push(EventKind::Killed {
+                session_id: old.id.clone(),
+                session_name: old.name.clone(),
+            });
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::fixtures;
+
+    fn session(id: &str, name: &str, status: ClaudeCodeStatus, title: &str) -> Session {
+        let mut session = fixtures::session(id, status);
+        session.name = name.to_string();
+        session.panes[0].title = title.to_string();
+        session.pane_title = title.to_string();
+        session
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let sessions = [session("$1", "work", ClaudeCodeStatus::Idle, "a")];
+        assert!(diff(&sessions, &sessions).is_empty());
+    }
+
+    #[test]
+    fn test_created_and_killed() {
+        let before = [session("$1", "old", ClaudeCodeStatus::Idle, "")];
+        let after = [session("$2", "new", ClaudeCodeStatus::Idle, "")];
+        assert_eq!(
+            diff(&before, &after),
+            [
+                EventKind::Created {
+                    session_id: "$2".to_string(),
+                    session_name: "new".to_string(),
+                },
+                EventKind::Killed {
+                    session_id: "$1".to_string(),
+                    session_name: "old".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_session_changes() {
+        let before = [session("$1", "work", ClaudeCodeStatus::Working, "a")];
+        let mut after = [session("$1", "play", ClaudeCodeStatus::WaitingInput, "b")];
+        after[0].attached = true;
+
+        let events = diff(&before, &after);
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[0], EventKind::Renamed { ref new_name, .. } if new_name == "play"));
+        assert!(matches!(events[1], EventKind::Attached { .. }));
+        assert!(matches!(
+            events[2],
+            EventKind::Status {
+                old: ClaudeCodeStatus::Working,
+                new: ClaudeCodeStatus::WaitingInput,
+                ..
+            }
+        ));
+        assert!(matches!(events[3], EventKind::Title { ref new, .. } if new == "b"));
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = Event {
+            time: 5,
+            kind: EventKind::Detached {
+                session_id: "$1".to_string(),
+                session_name: "work".to_string(),
+            },
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"time":5,"event":"detached","session_id":"$1","session_name":"work"}"#
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Background refresh worker
+//!
+//! Session discovery, status detection and preview capture all spawn tmux
+//! processes. The worker runs them on its own thread and sends the results
+//! to the UI as [`Update`]s, so key handling never waits on tmux.
+//!
+//! When a tmux control-mode client can be attached, session changes arrive
+//! as notifications and the full discovery only runs as a slow fallback.
+//! The client follows the previewed pane's session, so the preview is
+//! captured when that pane produces output. Agent panes in other sessions
+//! get no output notifications and are re-checked at the regular interval.
+//! Without a control client the worker polls at the regular interval.
+//!
+//! While a `tsm daemon` is running, the UI's worker reads the daemon's
+//! snapshot instead of discovering sessions itself.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::control::{ControlClient, ControlEvent};
+use crate::daemon::{self, DaemonSnapshot};
+use crate::discovery::Discovery;
+use crate::hooks;
+use crate::session::{unix_now, ClaudeCodeStatus, Question, Session};
+use crate::tmux::Tmux;
+
+/// Number of lines captured for the preview pane
+pub const PREVIEW_LINES: usize = 15;
+
+/// Full refresh interval while the control client delivers notifications
+const CONTROL_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to wait before retrying a control client that exited right away
+const CONTROL_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A control client that exits sooner than this is considered unsupported
+const CONTROL_MIN_LIFETIME: Duration = Duration::from_secs(1);
+
+/// Minimum time between status checks of a pane that keeps producing output
+const OUTPUT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// A request to the worker thread
+enum Request {
+    /// Refresh immediately instead of waiting for the next interval
+    Refresh,
+    /// Change which pane is captured for the preview
+    Preview(Option<String>),
+    /// A notification from the control client with the given generation
+    Control(u64, ControlEvent),
+}
+
+/// A full session refresh
+pub struct Snapshot {
+    /// Discovered sessions, or the error message if discovery failed
+    pub sessions: Result<Vec<Session>, String>,
+    /// Whether changes are being pushed by a control-mode client
+    pub live: bool,
+    /// Whether the sessions came from a running daemon
+    pub from_daemon: bool,
+    /// When the refresh finished
+    pub taken_at: Instant,
+}
+
+/// A change sent from the worker to the UI
+pub enum Update {
+    /// The full session list was refreshed
+    Sessions(Snapshot),
+    /// A session was renamed
+    SessionRenamed { session_id: String, name: String },
+    /// A Claude Code pane's status was re-detected
+    PaneStatus {
+        pane_id: String,
+        status: ClaudeCodeStatus,
+        question: Option<Question>,
+    },
+    /// The preview pane was captured
+    Preview {
+        pane_id: Option<String>,
+        content: Option<String>,
+    },
+}
+
+/// Handle to the background refresh thread.
+/// The thread exits when the handle is dropped.
+pub struct Worker {
+    requests: Sender<Request>,
+    updates: Receiver<Update>,
+}
+
+impl Worker {
+    /// Start the worker, polling every `interval` unless control mode is available.
+    /// With `use_daemon`, a running daemon's snapshot is used instead of `discovery`.
+    pub fn spawn(discovery: Discovery, interval: Duration, use_daemon: bool) -> Self {
+        let (request_tx, request_rx) = mpsc::channel();
+        let (update_tx, update_rx) = mpsc::channel();
+
+        let state = State::new(
+            discovery,
+            request_tx.clone(),
+            update_tx,
+            interval,
+            use_daemon,
+        );
+        thread::spawn(move || state.run(request_rx));
+
+        Self {
+            requests: request_tx,
+            updates: update_rx,
+        }
+    }
+
+    /// Ask for an immediate refresh
+    pub fn request_refresh(&self) {
+        let _ = self.requests.send(Request::Refresh);
+    }
+
+    /// Change the pane captured for the preview
+    pub fn set_preview_pane(&self, pane_id: Option<String>) {
+        let _ = self.requests.send(Request::Preview(pane_id));
+    }
+
+    /// Return all updates received since the last call, oldest first
+    pub fn updates(&self) -> Vec<Update> {
+        self.updates.try_iter().collect()
+    }
+}
+
+/// State owned by the worker thread
+struct State {
+    discovery: Discovery,
+    interval: Duration,
+    /// Read a running daemon's snapshot instead of discovering sessions
+    use_daemon: bool,
+    /// Sender handed to control clients so their events reach the loop
+    requests: Sender<Request>,
+    updates: Sender<Update>,
+    control: Option<ControlClient>,
+    /// Incremented for each control client so stale events can be ignored
+    control_generation: u64,
+    control_started_at: Instant,
+    control_retry_at: Instant,
+    /// Panes running an agent as of the last full refresh
+    claude_panes: HashSet<String>,
+    /// Session ID of every pane as of the last full refresh
+    pane_sessions: HashMap<String, String>,
+    /// Claude panes with output that haven't been re-checked yet
+    dirty_panes: HashSet<String>,
+    /// When each Claude pane's status was last re-checked after output
+    last_checked: HashMap<String, Instant>,
+    preview_pane: Option<String>,
+    next_refresh: Instant,
+    next_preview: Instant,
+    /// Next re-check of agent panes outside the followed session
+    next_pane_poll: Instant,
+}
+
+impl State {
+    fn new(
+        discovery: Discovery,
+        requests: Sender<Request>,
+        updates: Sender<Update>,
+        interval: Duration,
+        use_daemon: bool,
+    ) -> Self {
+        let now = Instant::now();
+        Self {
+            discovery,
+            interval,
+            use_daemon,
+            requests,
+            updates,
+            control: None,
+            control_generation: 0,
+            control_started_at: now,
+            control_retry_at: now,
+            claude_panes: HashSet::new(),
+            pane_sessions: HashMap::new(),
+            dirty_panes: HashSet::new(),
+            last_checked: HashMap::new(),
+            preview_pane: None,
+            next_refresh: now,
+            next_preview: now,
+            next_pane_poll: now,
+        }
+    }
+
+    /// Worker thread main loop. Returns when the UI has gone away.
+    fn run(mut self, requests: Receiver<Request>) {
+        loop {
+            let now = Instant::now();
+
+            if now >= self.next_refresh && !self.refresh_sessions() {
+                return;
+            }
+
+            if now >= self.next_preview {
+                let content = self
+                    .preview_pane
+                    .as_ref()
+                    .and_then(|id| Tmux::capture_pane(id, PREVIEW_LINES, false).ok());
+                let update = Update::Preview {
+                    pane_id: self.preview_pane.clone(),
+                    content,
+                };
+                if self.updates.send(update).is_err() {
+                    return;
+                }
+                // A followed pane is captured on output, polling is a fallback
+                self.next_preview = now
+                    + if self.preview_followed() {
+                        CONTROL_POLL_INTERVAL
+                    } else {
+                        self.interval
+                    };
+            }
+
+            if self.control.is_some() && now >= self.next_pane_poll {
+                self.mark_unfollowed_panes();
+                self.next_pane_poll = now + self.interval;
+            }
+
+            // Re-check Claude panes that produced output, at most once per debounce period
+            let due: Vec<String> = self
+                .dirty_panes
+                .iter()
+                .filter(|id| {
+                    !matches!(self.last_checked.get(*id), Some(t) if t.elapsed() < OUTPUT_DEBOUNCE)
+                })
+                .cloned()
+                .collect();
+            for pane_id in due {
+                self.dirty_panes.remove(&pane_id);
+                let (status, question) = self.discovery.refresh_pane_status(&pane_id);
+                self.last_checked.insert(pane_id.clone(), Instant::now());
+                if self
+                    .updates
+                    .send(Update::PaneStatus {
+                        pane_id,
+                        status,
+                        question,
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+            }
+
+            // Sleep until the next deadline, waking early for requests
+            let mut deadline = self.next_refresh.min(self.next_preview);
+            if self.control.is_some() {
+                deadline = deadline.min(self.next_pane_poll);
+            }
+            if !self.dirty_panes.is_empty() {
+                deadline = deadline.min(Instant::now() + OUTPUT_DEBOUNCE);
+            }
+            let timeout = deadline.saturating_duration_since(Instant::now());
+
+            match requests.recv_timeout(timeout) {
+                Ok(request) => {
+                    for request in std::iter::once(request).chain(requests.try_iter()) {
+                        if !self.handle(request) {
+                            return;
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    /// Handle one request. Returns false when the UI has gone away.
+    fn handle(&mut self, request: Request) -> bool {
+        let now = Instant::now();
+
+        match request {
+            Request::Refresh => self.next_refresh = now,
+            Request::Preview(pane_id) => {
+                self.preview_pane = pane_id;
+                self.next_preview = now;
+                self.follow_preview_session();
+            }
+            Request::Control(generation, _) if generation != self.control_generation => {}
+            Request::Control(_, event) => match event {
+                ControlEvent::Layout => self.next_refresh = now,
+                ControlEvent::SessionRenamed { session_id, name } => {
+                    return self
+                        .updates
+                        .send(Update::SessionRenamed { session_id, name })
+                        .is_ok();
+                }
+                ControlEvent::Output { pane_id } => {
+                    if self.preview_pane.as_ref() == Some(&pane_id) {
+                        self.next_preview = self.next_preview.min(now + OUTPUT_DEBOUNCE);
+                    }
+                    if self.claude_panes.contains(&pane_id) {
+                        self.dirty_panes.insert(pane_id);
+                    }
+                }
+                ControlEvent::Exit => {
+                    // Reconnect right away if the attached session went away,
+                    // back off if control mode doesn't work at all
+                    let lived = self.control_started_at.elapsed();
+                    self.control = None;
+                    self.control_retry_at = if lived < CONTROL_MIN_LIFETIME {
+                        now + CONTROL_RETRY_INTERVAL
+                    } else {
+                        now
+                    };
+                    self.next_refresh = now;
+                }
+            },
+        }
+
+        true
+    }
+
+    /// Run a full discovery and send it to the UI.
+    /// Returns false when the UI has gone away.
+    fn refresh_sessions(&mut self) -> bool {
+        if self.use_daemon {
+            if let Some(snapshot) = daemon::read_snapshot() {
+                return self.use_daemon_snapshot(snapshot);
+            }
+        }
+
+        let sessions = self.discovery.list_sessions().map_err(|e| e.to_string());
+
+        if let Ok(ref sessions) = sessions {
+            // Only long-running views clean up; one-shot commands like
+            // `tsm list` leave the state directory alone
+            let pane_ids: HashSet<&str> = sessions
+                .iter()
+                .flat_map(|s| s.panes.iter().map(|p| p.id.as_str()))
+                .collect();
+            hooks::prune(&pane_ids);
+
+            self.claude_panes = sessions
+                .iter()
+                .flat_map(|s| s.agent_panes())
+                .map(|p| p.id.clone())
+                .collect();
+            self.pane_sessions = sessions
+                .iter()
+                .flat_map(|s| s.panes.iter().map(|p| (p.id.clone(), s.id.clone())))
+                .collect();
+            self.dirty_panes.retain(|id| self.claude_panes.contains(id));
+            self.last_checked
+                .retain(|id, _| self.claude_panes.contains(id));
+            self.ensure_control_client(sessions);
+            self.follow_preview_session();
+        }
+
+        let live = self.control.is_some();
+        let snapshot = Snapshot {
+            sessions,
+            live,
+            from_daemon: false,
+            taken_at: Instant::now(),
+        };
+
+        self.next_refresh = Instant::now()
+            + if live {
+                CONTROL_POLL_INTERVAL
+            } else {
+                self.interval
+            };
+
+        self.updates.send(Update::Sessions(snapshot)).is_ok()
+    }
+
+    /// Pass on the daemon's sessions. The control client and pane checks
+    /// are not needed while the daemon does the work.
+    fn use_daemon_snapshot(&mut self, snapshot: DaemonSnapshot) -> bool {
+        self.control = None;
+        self.claude_panes.clear();
+        self.dirty_panes.clear();
+
+        // The snapshot is as old as the daemon's last write, not this read
+        let age = Duration::from_secs(unix_now().saturating_sub(snapshot.updated_at));
+        let now = Instant::now();
+        let update = Update::Sessions(Snapshot {
+            sessions: Ok(snapshot.sessions),
+            live: false,
+            from_daemon: true,
+            taken_at: now.checked_sub(age).unwrap_or(now),
+        });
+        self.next_refresh = Instant::now() + self.interval;
+        self.updates.send(update).is_ok()
+    }
+
+    /// Session of the preview pane, if known
+    fn preview_session(&self) -> Option<&String> {
+        self.pane_sessions.get(self.preview_pane.as_ref()?)
+    }
+
+    /// Whether the control client reports the preview pane's output
+    fn preview_followed(&self) -> bool {
+        match (&self.control, self.preview_session()) {
+            (Some(control), Some(session_id)) => control.session_id() == session_id,
+            _ => false,
+        }
+    }
+
+    /// Move the control client to the preview pane's session
+    fn follow_preview_session(&mut self) {
+        let Some(session_id) = self.preview_session().cloned() else {
+            return;
+        };
+        let Some(ref mut control) = self.control else {
+            return;
+        };
+        if control.session_id() != session_id {
+            let _ = control.switch_session(&session_id);
+        }
+    }
+
+    /// Queue a status re-check of every agent pane the control client
+    /// doesn't report output for
+    fn mark_unfollowed_panes(&mut self) {
+        let Some(ref control) = self.control else {
+            return;
+        };
+        let followed = control.session_id();
+        let unfollowed = self
+            .claude_panes
+            .iter()
+            .filter(|id| self.pane_sessions.get(*id).is_none_or(|s| s != followed))
+            .cloned();
+        self.dirty_panes.extend(unfollowed);
+    }
+
+    /// Attach a control client if none is running and it's time to try
+    fn ensure_control_client(&mut self, sessions: &[Session]) {
+        if self.control.is_some() || Instant::now() < self.control_retry_at {
+            return;
+        }
+        // Prefer the previewed session, then one with Claude Code, so their
+        // %output can be followed
+        let preview_session = self.preview_session();
+        let Some(session) = sessions
+            .iter()
+            .find(|s| Some(&s.id) == preview_session)
+            .or_else(|| sessions.iter().find(|s| s.claude_code_pane.is_some()))
+            .or_else(|| sessions.first())
+        else {
+            return;
+        };
+
+        self.control_generation += 1;
+        let generation = self.control_generation;
+        let requests = self.requests.clone();
+
+        match ControlClient::spawn(&session.id, move |event| {
+            let _ = requests.send(Request::Control(generation, event));
+        }) {
+            Ok(client) => {
+                self.control = Some(client);
+                self.control_started_at = Instant::now();
+            }
+            Err(_) => self.control_retry_at = Instant::now() + CONTROL_RETRY_INTERVAL,
+        }
+    }
+}